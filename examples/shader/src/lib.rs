@@ -1,13 +1,24 @@
 use std::ffi::CString;
 
 use kinc::{
-    g4::{
-        ClearMode, IndexBuffer, Pipeline, PipelineBuilder, VertexBuffer, VertexBufferDesc,
-        VertexStructureBuilder,
-    },
-    Callbacks, Kinc,
+    g4::{ClearMode, IndexBuffer, Pipeline, PipelineBuilder, VertexBuffer, VertexBufferDesc},
+    math::Vec3,
+    Callbacks, Color, Kinc,
 };
 
+kinc::shader_interface!(
+    vertex,
+    "#version 450
+
+in vec3 pos;
+
+void main() {
+	gl_Position = vec4(pos.x, pos.y, 0.5, 1.0);
+}",
+    bytecode = VERTEX_SHADER,
+    vertex = Vertex
+);
+
 struct Shader {
     // vertex_structure: &'static VertexStructure,
     pipeline: Pipeline,
@@ -21,7 +32,7 @@ impl Callbacks for Shader {
         let window = kinc.default_window();
         {
             let mut pass = g4.begin(&window);
-            pass.clear(ClearMode::COLOR, 0xFF0FFFFF, 1.0, 1);
+            pass.clear(ClearMode::COLOR, Color::from_argb(0xFF0FFFFF), 1.0, 1);
             pass.set_vertex_buffer(&self.vertex_buffer);
             pass.set_index_buffer(&self.index_buffer);
             pass.set_pipeline(&self.pipeline);
@@ -42,17 +53,8 @@ pub fn rust_kickstart() {
 pub fn real_main() -> Result<(), Box<dyn std::error::Error>> {
     let (kinc, _) = kinc::KincBuilder::new(&CString::new("Kinc + Rust")?, 500, 500).build();
 
-    let vb = kinc::compile_shader!(
-        vertex,
-        "#version 450
-
-in vec3 pos;
-
-void main() {
-	gl_Position = vec4(pos.x, pos.y, 0.5, 1.0);
-}"
-    );
-    let str = String::from_utf8((&vb).to_vec()).unwrap();
+    let vb = VERTEX_SHADER;
+    let str = String::from_utf8(vb.to_vec()).unwrap();
     println!("{str}");
 
     let fb = kinc::compile_shader!(
@@ -68,10 +70,7 @@ void main() {
 
     let vertex_shader = kinc::g4::Shader::new(vb, kinc::g4::ShaderType::Vertex);
     let fragment_shader = kinc::g4::Shader::new(fb, kinc::g4::ShaderType::Fragment);
-    let str = CString::new("pos")?;
-    let vertex_structure = VertexStructureBuilder::new()
-        .add(&str, kinc::g4::VertexData::F32_3X)
-        .build();
+    let vertex_structure = Vertex::structure();
     let pipeline = PipelineBuilder::new(
         &vertex_shader,
         &fragment_shader,
@@ -86,9 +85,17 @@ void main() {
         usage: kinc::g4::Usage::Static,
         instance_data_step_rate: 0,
     });
-    vertex_buffer
-        .lock_all::<f32>()
-        .copy_from_slice(&[-0.5, -0.5, 0.0, 0.5, -0.5, 0.0, 0.0, 0.5, 0.0]);
+    vertex_buffer.lock_all::<Vertex>().copy_from_slice(&[
+        Vertex {
+            pos: Vec3::new(-0.5, -0.5, 0.0),
+        },
+        Vertex {
+            pos: Vec3::new(0.5, -0.5, 0.0),
+        },
+        Vertex {
+            pos: Vec3::new(0.0, 0.5, 0.0),
+        },
+    ]);
     let index_buffer =
         IndexBuffer::new(3, kinc::g4::Usage::Static, kinc::g4::IndexBufferFormat::U32);
     index_buffer.lock::<u32>().copy_from_slice(&[0, 1, 2]);
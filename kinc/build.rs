@@ -299,6 +299,14 @@ fn main() {
         }
     }
 
+    if env::var("CARGO_FEATURE_VR").is_ok() {
+        // KORE_VR alone only declares Kinc's VR functions, the headset backends
+        // (Oculus, SteamVR) and their SDKs aren't built or linked yet
+        panic!(
+            "The vr feature has no headset backend yet, build without it to get the no-op kinc::vr"
+        );
+    }
+
     let graphics = if env::var("CARGO_FEATURE_OPENGL").is_ok() {
         GraphicsApi::OpenGL
     } else if env::var("CARGO_FEATURE_VULKAN").is_ok() {
@@ -441,6 +449,12 @@ fn main() {
         }
     }
 
+    // Small additions to Kinc's API, see shim/shim.h
+    println!("cargo:rerun-if-changed=shim");
+    add(&mut files, "shim/framebuffer.c");
+    add(&mut files, "shim/socket.c");
+    add(&mut files, "shim/log.c");
+
     let bindings = {
         let mut builder = bindgen::Builder::default().header("kinc.h").clang_args(
             include_paths
@@ -517,6 +531,31 @@ fn main() {
         .write_to_file(out_path.join("bindings.rs"))
         .expect("Couldn't write bindings!");
 
+    // Kinc's own units are compiled through wrappers that include shim/log.h first,
+    // so that what Kinc logs reaches the hook in shim/log.c with the file and line of the call
+    let manifest_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap());
+    let units_dir = out_path.join("units");
+    fs::create_dir_all(&units_dir).unwrap();
+    let include = |path: &Path| format!("#include \"{}\"\n", path.display()).replace('\\', "/");
+    let files: Vec<String> = files
+        .into_iter()
+        .map(|file| {
+            if !file.starts_with("Kinc/") {
+                return file;
+            }
+            let mut unit = String::new();
+            if file == "Kinc/Sources/kinc/rootunit.c" {
+                // So that shim/log.h compiles the definitions in kinc/error.h and kinc/log.h
+                unit.push_str("#define KINC_IMPLEMENTATION_ROOT\n");
+            }
+            unit.push_str(&include(&manifest_dir.join("shim/log.h")));
+            unit.push_str(&include(&manifest_dir.join(&file)));
+            let wrapper = units_dir.join(file.replace('/', "_"));
+            fs::write(&wrapper, unit).unwrap();
+            wrapper.display().to_string()
+        })
+        .collect();
+
     let mut builder = cc::Build::new();
 
     builder.files(files);
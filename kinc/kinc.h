@@ -68,3 +68,5 @@
 #include <kinc/compute/compute.h>
 #include <kinc/simd/float32x4.h>
 #include <kinc/color.h>
+
+#include "shim/shim.h"
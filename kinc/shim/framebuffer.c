@@ -0,0 +1,28 @@
+#include "shim.h"
+
+#include <kinc/window.h>
+
+#ifdef KORE_OPENGL
+#include <kinc/backend/graphics4/ogl.h>
+#endif
+
+bool kinc_rust_framebuffer_get_pixels(int window, uint8_t *data) {
+#ifdef KORE_OPENGL
+	while (glGetError() != GL_NO_ERROR) {
+	}
+
+	// A render target may be bound, the window's framebuffer is always 0
+	GLint framebuffer = 0;
+	glGetIntegerv(GL_FRAMEBUFFER_BINDING, &framebuffer);
+	glBindFramebuffer(GL_FRAMEBUFFER, 0);
+	glPixelStorei(GL_PACK_ALIGNMENT, 1);
+	glReadPixels(0, 0, kinc_window_width(window), kinc_window_height(window), GL_RGBA, GL_UNSIGNED_BYTE, data);
+	glBindFramebuffer(GL_FRAMEBUFFER, (GLuint)framebuffer);
+
+	return glGetError() == GL_NO_ERROR;
+#else
+	(void)window;
+	(void)data;
+	return false;
+#endif
+}
@@ -0,0 +1,116 @@
+#include "log.h"
+#include "shim.h"
+
+#include <errno.h>
+#include <stdio.h>
+#include <stdlib.h>
+
+#ifdef _WIN32
+#include <windows.h>
+#endif
+
+// This file forwards to the real functions
+#undef kinc_log
+#undef kinc_log_args
+#undef kinc_affirm
+#undef kinc_affirm_message
+#undef kinc_affirm_args
+#undef kinc_error
+#undef kinc_error_message
+#undef kinc_error_args
+
+static kinc_rust_log_hook hook = NULL;
+
+void kinc_rust_set_log_hook(kinc_rust_log_hook new_hook) {
+	hook = new_hook;
+}
+
+static void report_args(const char *file, int line, kinc_log_level_t level, bool error, const char *format, va_list args) {
+	if (hook == NULL) {
+		return;
+	}
+	va_list copy;
+	va_copy(copy, args);
+	int length = vsnprintf(NULL, 0, format, copy);
+	va_end(copy);
+	char *message = length < 0 ? NULL : (char *)malloc((size_t)length + 1);
+	if (message == NULL) {
+		hook(level, file, line, format, error);
+		return;
+	}
+	vsnprintf(message, (size_t)length + 1, format, args);
+	hook(level, file, line, message, error);
+	free(message);
+}
+
+static void report(const char *file, int line, kinc_log_level_t level, bool error, const char *format, ...) {
+	va_list args;
+	va_start(args, format);
+	report_args(file, line, level, error, format, args);
+	va_end(args);
+}
+
+void kinc_rust_log_args(const char *file, int line, kinc_log_level_t level, const char *format, va_list args) {
+	// Kinc logs from inside calls whose errors are read afterwards, see shim/socket.c
+	int saved_errno = errno;
+#ifdef _WIN32
+	DWORD saved_error = GetLastError();
+#endif
+	if (hook == NULL) {
+		kinc_log_args(level, format, args);
+	}
+	else {
+		report_args(file, line, level, false, format, args);
+	}
+#ifdef _WIN32
+	SetLastError(saved_error);
+#endif
+	errno = saved_errno;
+}
+
+void kinc_rust_log(const char *file, int line, kinc_log_level_t level, const char *format, ...) {
+	va_list args;
+	va_start(args, format);
+	kinc_rust_log_args(file, line, level, format, args);
+	va_end(args);
+}
+
+void kinc_rust_error_args(const char *file, int line, const char *format, va_list args) {
+	va_list copy;
+	va_copy(copy, args);
+	report_args(file, line, KINC_LOG_LEVEL_ERROR, true, format, copy);
+	va_end(copy);
+	kinc_error_args(format, args);
+}
+
+void kinc_rust_error_message(const char *file, int line, const char *format, ...) {
+	va_list args;
+	va_start(args, format);
+	kinc_rust_error_args(file, line, format, args);
+	va_end(args);
+}
+
+void kinc_rust_error(const char *file, int line) {
+	report(file, line, KINC_LOG_LEVEL_ERROR, true, "Error");
+	kinc_error();
+}
+
+void kinc_rust_affirm_args(const char *file, int line, bool condition, const char *format, va_list args) {
+	if (!condition) {
+		kinc_rust_error_args(file, line, format, args);
+	}
+}
+
+void kinc_rust_affirm_message(const char *file, int line, bool condition, const char *format, ...) {
+	va_list args;
+	va_start(args, format);
+	kinc_rust_affirm_args(file, line, condition, format, args);
+	va_end(args);
+}
+
+void kinc_rust_affirm(const char *file, int line, bool condition) {
+	if (!condition) {
+		report(file, line, KINC_LOG_LEVEL_ERROR, true, "Affirmation failed");
+		kinc_affirm(condition);
+	}
+}
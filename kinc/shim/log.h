@@ -0,0 +1,38 @@
+#pragma once
+
+// Included ahead of each of Kinc's units by build.rs,
+// so that Kinc's log messages and errors reach kinc_rust_set_log_hook with the file and line of the call.
+// kinc/error.h and kinc/log.h come first so that their declarations, and in the root unit their definitions,
+// are compiled before the macros below exist.
+
+#include <kinc/error.h>
+#include <kinc/log.h>
+
+#include <stdarg.h>
+#include <stdbool.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+void kinc_rust_log(const char *file, int line, kinc_log_level_t level, const char *format, ...);
+void kinc_rust_log_args(const char *file, int line, kinc_log_level_t level, const char *format, va_list args);
+void kinc_rust_affirm(const char *file, int line, bool condition);
+void kinc_rust_affirm_message(const char *file, int line, bool condition, const char *format, ...);
+void kinc_rust_affirm_args(const char *file, int line, bool condition, const char *format, va_list args);
+void kinc_rust_error(const char *file, int line);
+void kinc_rust_error_message(const char *file, int line, const char *format, ...);
+void kinc_rust_error_args(const char *file, int line, const char *format, va_list args);
+
+#ifdef __cplusplus
+}
+#endif
+
+#define kinc_log(level, ...) kinc_rust_log(__FILE__, __LINE__, level, __VA_ARGS__)
+#define kinc_log_args(level, format, args) kinc_rust_log_args(__FILE__, __LINE__, level, format, args)
+#define kinc_affirm(condition) kinc_rust_affirm(__FILE__, __LINE__, condition)
+#define kinc_affirm_message(condition, ...) kinc_rust_affirm_message(__FILE__, __LINE__, condition, __VA_ARGS__)
+#define kinc_affirm_args(condition, format, args) kinc_rust_affirm_args(__FILE__, __LINE__, condition, format, args)
+#define kinc_error() kinc_rust_error(__FILE__, __LINE__)
+#define kinc_error_message(...) kinc_rust_error_message(__FILE__, __LINE__, __VA_ARGS__)
+#define kinc_error_args(format, args) kinc_rust_error_args(__FILE__, __LINE__, format, args)
@@ -0,0 +1,44 @@
+#pragma once
+
+// Additions to Kinc's C API that the Rust wrapper needs, compiled together with Kinc in build.rs.
+
+#include <kinc/log.h>
+#include <kinc/network/socket.h>
+
+#include <stdbool.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+// Reads what has been drawn into the window's framebuffer so far as RGBA8, bottom row first.
+// data must hold width * height * 4 bytes of the window's size.
+// Returns false if the graphics backend can't read back its framebuffer.
+bool kinc_rust_framebuffer_get_pixels(int window, uint8_t *data);
+
+// Returned by the socket calls below when a non-blocking socket would have blocked.
+#define KINC_RUST_SOCKET_WOULD_BLOCK (-2)
+
+// Kinc's socket calls, except that failures return -1, or KINC_RUST_SOCKET_WOULD_BLOCK if the socket would have blocked,
+// which Kinc doesn't report. errno (WSAGetLastError on Windows) is checked as soon as Kinc returns,
+// and Kinc's logging in between leaves it alone, see shim/log.c.
+// kinc_rust_socket_accept returns 0 on success.
+int kinc_rust_socket_accept(kinc_socket_t *socket, kinc_socket_t *new_socket, unsigned *remote_address, unsigned *remote_port);
+int kinc_rust_socket_send_connected(kinc_socket_t *socket, const uint8_t *data, int size);
+int kinc_rust_socket_send_address(kinc_socket_t *socket, unsigned address, int port, const uint8_t *data, int size);
+int kinc_rust_socket_send_url(kinc_socket_t *socket, const char *url, int port, const uint8_t *data, int size);
+int kinc_rust_socket_receive_connected(kinc_socket_t *socket, uint8_t *data, int max_size);
+int kinc_rust_socket_receive(kinc_socket_t *socket, uint8_t *data, int max_size, unsigned *from_address, unsigned *from_port);
+
+// Receives what Kinc logs, with the file and line of the call. error is set for kinc_error and failed kinc_affirm calls,
+// Kinc's own error handling, which ends the program, only continues if the hook returns.
+typedef void (*kinc_rust_log_hook)(kinc_log_level_t level, const char *file, int line, const char *message, bool error);
+
+// Sends Kinc's log messages and errors to hook instead of kinc_log_args, NULL restores Kinc's own logging.
+// Set it before starting threads that use Kinc.
+void kinc_rust_set_log_hook(kinc_rust_log_hook hook);
+
+#ifdef __cplusplus
+}
+#endif
@@ -0,0 +1,61 @@
+#include "shim.h"
+
+#ifdef _WIN32
+#include <winsock2.h>
+#else
+#include <errno.h>
+#endif
+
+// Cleared before each call, so that an error left over from an earlier call isn't mistaken for this one's
+static void clear_error(void) {
+#ifdef _WIN32
+	WSASetLastError(0);
+#else
+	errno = 0;
+#endif
+}
+
+static bool would_block(void) {
+#ifdef _WIN32
+	return WSAGetLastError() == WSAEWOULDBLOCK;
+#else
+	return errno == EAGAIN || errno == EWOULDBLOCK;
+#endif
+}
+
+static int result(int value) {
+	if (value >= 0) {
+		return value;
+	}
+	return would_block() ? KINC_RUST_SOCKET_WOULD_BLOCK : -1;
+}
+
+int kinc_rust_socket_accept(kinc_socket_t *socket, kinc_socket_t *new_socket, unsigned *remote_address, unsigned *remote_port) {
+	clear_error();
+	return result(kinc_socket_accept(socket, new_socket, remote_address, remote_port) ? 0 : -1);
+}
+
+int kinc_rust_socket_send_connected(kinc_socket_t *socket, const uint8_t *data, int size) {
+	clear_error();
+	return result(kinc_socket_send_connected(socket, data, size));
+}
+
+int kinc_rust_socket_send_address(kinc_socket_t *socket, unsigned address, int port, const uint8_t *data, int size) {
+	clear_error();
+	return result(kinc_socket_send_address(socket, address, port, data, size));
+}
+
+int kinc_rust_socket_send_url(kinc_socket_t *socket, const char *url, int port, const uint8_t *data, int size) {
+	clear_error();
+	return result(kinc_socket_send_url(socket, url, port, data, size));
+}
+
+int kinc_rust_socket_receive_connected(kinc_socket_t *socket, uint8_t *data, int max_size) {
+	clear_error();
+	return result(kinc_socket_receive_connected(socket, data, max_size));
+}
+
+int kinc_rust_socket_receive(kinc_socket_t *socket, uint8_t *data, int max_size, unsigned *from_address, unsigned *from_port) {
+	clear_error();
+	return result(kinc_socket_receive(socket, data, max_size, from_address, from_port));
+}
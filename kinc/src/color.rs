@@ -0,0 +1,256 @@
+use core::str::FromStr;
+
+use crate::sys::*;
+
+/// A color with 8 bits per channel, stored in the ARGB order Kinc uses.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Color(u32);
+
+impl Color {
+    pub const BLACK: Self = Self(KINC_COLOR_BLACK);
+    pub const WHITE: Self = Self(KINC_COLOR_WHITE);
+    pub const RED: Self = Self(KINC_COLOR_RED);
+    pub const GREEN: Self = Self(KINC_COLOR_GREEN);
+    pub const BLUE: Self = Self(KINC_COLOR_BLUE);
+    pub const MAGENTA: Self = Self(KINC_COLOR_MAGENTA);
+    pub const YELLOW: Self = Self(KINC_COLOR_YELLOW);
+    pub const CYAN: Self = Self(KINC_COLOR_CYAN);
+    pub const TRANSPARENT: Self = Self(0);
+
+    pub const fn rgba8(red: u8, green: u8, blue: u8, alpha: u8) -> Self {
+        Self((alpha as u32) << 24 | (red as u32) << 16 | (green as u32) << 8 | blue as u32)
+    }
+
+    pub const fn rgb8(red: u8, green: u8, blue: u8) -> Self {
+        Self::rgba8(red, green, blue, 0xff)
+    }
+
+    /// Creates a color from channels between 0 and 1, values outside of that range are clamped.
+    pub fn rgba(red: f32, green: f32, blue: f32, alpha: f32) -> Self {
+        fn unorm8(value: f32) -> u8 {
+            (value.clamp(0.0, 1.0) * 255.0 + 0.5) as u8
+        }
+        Self::rgba8(unorm8(red), unorm8(green), unorm8(blue), unorm8(alpha))
+    }
+
+    pub fn rgb(red: f32, green: f32, blue: f32) -> Self {
+        Self::rgba(red, green, blue, 1.0)
+    }
+
+    /// Interprets `argb` as `0xAARRGGBB`.
+    pub const fn from_argb(argb: u32) -> Self {
+        Self(argb)
+    }
+
+    /// Interprets `rgba` as `0xRRGGBBAA`.
+    pub const fn from_rgba(rgba: u32) -> Self {
+        Self(rgba.rotate_right(8))
+    }
+
+    /// Returns the color as `0xAARRGGBB`.
+    pub const fn to_argb(self) -> u32 {
+        self.0
+    }
+
+    /// Returns the color as `0xRRGGBBAA`.
+    pub const fn to_rgba(self) -> u32 {
+        self.0.rotate_left(8)
+    }
+
+    pub const fn red(self) -> u8 {
+        (self.0 >> 16) as u8
+    }
+
+    pub const fn green(self) -> u8 {
+        (self.0 >> 8) as u8
+    }
+
+    pub const fn blue(self) -> u8 {
+        self.0 as u8
+    }
+
+    pub const fn alpha(self) -> u8 {
+        (self.0 >> 24) as u8
+    }
+
+    pub const fn with_alpha(self, alpha: u8) -> Self {
+        Self(self.0 & 0x00ffffff | (alpha as u32) << 24)
+    }
+
+    /// Returns red, green, blue and alpha between 0 and 1.
+    pub fn components(self) -> [f32; 4] {
+        let (mut red, mut green, mut blue, mut alpha) = (0.0, 0.0, 0.0, 0.0);
+        // Safety: all pointers are valid for writes
+        unsafe { kinc_color_components(self.0, &mut red, &mut green, &mut blue, &mut alpha) };
+        [red, green, blue, alpha]
+    }
+
+    /// Converts the color channels from sRGB to linear, alpha is kept as is.
+    pub fn to_linear(self) -> Self {
+        let [red, green, blue, alpha] = self.components();
+        Self::rgba(
+            srgb_to_linear(red),
+            srgb_to_linear(green),
+            srgb_to_linear(blue),
+            alpha,
+        )
+    }
+
+    /// Converts the color channels from linear to sRGB, alpha is kept as is.
+    pub fn to_srgb(self) -> Self {
+        let [red, green, blue, alpha] = self.components();
+        Self::rgba(
+            linear_to_srgb(red),
+            linear_to_srgb(green),
+            linear_to_srgb(blue),
+            alpha,
+        )
+    }
+}
+
+fn pow(base: f32, exponent: f32) -> f32 {
+    // Safety: plain math function without side effects
+    unsafe { kinc_pow(base, exponent) }
+}
+
+fn srgb_to_linear(value: f32) -> f32 {
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        pow((value + 0.055) / 1.055, 2.4)
+    }
+}
+
+fn linear_to_srgb(value: f32) -> f32 {
+    if value <= 0.0031308 {
+        value * 12.92
+    } else {
+        1.055 * pow(value, 1.0 / 2.4) - 0.055
+    }
+}
+
+impl Into<u32> for Color {
+    fn into(self) -> u32 {
+        self.0
+    }
+}
+
+impl From<[f32; 4]> for Color {
+    fn from([red, green, blue, alpha]: [f32; 4]) -> Self {
+        Self::rgba(red, green, blue, alpha)
+    }
+}
+
+impl From<Color> for [f32; 4] {
+    fn from(color: Color) -> Self {
+        color.components()
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ColorParseError;
+
+impl core::fmt::Display for ColorParseError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "invalid hex color, expected #RGB, #RGBA, #RRGGBB or #RRGGBBAA"
+        )
+    }
+}
+
+impl core::error::Error for ColorParseError {}
+
+/// Parses CSS-style hex colors, `#RGB`, `#RGBA`, `#RRGGBB` or `#RRGGBBAA`, the `#` is optional.
+impl FromStr for Color {
+    type Err = ColorParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let hex = s.strip_prefix('#').unwrap_or(s);
+        if !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err(ColorParseError);
+        }
+        let value = u32::from_str_radix(hex, 16).map_err(|_| ColorParseError)?;
+        // Expands a 4 bit channel to 8 bits, 0xf becomes 0xff
+        let nibble = |shift: u32| ((value >> shift) & 0xf) as u8 * 0x11;
+        match hex.len() {
+            3 => Ok(Self::rgb8(nibble(8), nibble(4), nibble(0))),
+            4 => Ok(Self::rgba8(nibble(12), nibble(8), nibble(4), nibble(0))),
+            6 => Ok(Self::from_argb(0xff000000 | value)),
+            8 => Ok(Self::from_rgba(value)),
+            _ => Err(ColorParseError),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn channels_pack_in_argb_order() {
+        let color = Color::rgba8(0x12, 0x34, 0x56, 0x78);
+        assert_eq!(color.to_argb(), 0x78123456);
+        assert_eq!(color.to_rgba(), 0x12345678);
+        assert_eq!(Color::from_argb(0x78123456), color);
+        assert_eq!(Color::from_rgba(0x12345678), color);
+        assert_eq!(
+            (color.red(), color.green(), color.blue(), color.alpha()),
+            (0x12, 0x34, 0x56, 0x78)
+        );
+        assert_eq!(color.with_alpha(0xff), Color::rgb8(0x12, 0x34, 0x56));
+        assert_eq!(Into::<u32>::into(Color::RED), 0xffff0000);
+        assert_eq!(Color::BLUE, Color::rgb8(0, 0, 0xff));
+    }
+
+    #[test]
+    fn float_channels_are_clamped_and_rounded() {
+        assert_eq!(
+            Color::rgba(1.0, 0.5, -1.0, 2.0),
+            Color::rgba8(255, 128, 0, 255)
+        );
+        assert_eq!(Color::rgb(0.0, 1.0, 0.0), Color::GREEN);
+        let color = Color::rgba8(0, 51, 102, 255);
+        assert_eq!(color.components(), [0.0, 0.2, 0.4, 1.0]);
+        assert_eq!(Color::from(<[f32; 4]>::from(color)), color);
+    }
+
+    #[test]
+    fn hex_colors_parse() {
+        let parse = |s: &str| s.parse::<Color>();
+        assert_eq!(parse("#f0a"), Ok(Color::rgb8(0xff, 0x00, 0xaa)));
+        assert_eq!(parse("f0a8"), Ok(Color::rgba8(0xff, 0x00, 0xaa, 0x88)));
+        assert_eq!(parse("#12abEF"), Ok(Color::rgb8(0x12, 0xab, 0xef)));
+        assert_eq!(parse("#12abef80"), Ok(Color::rgba8(0x12, 0xab, 0xef, 0x80)));
+        for bad in [
+            "",
+            "#",
+            "#12",
+            "#12345",
+            "#1234567",
+            "#123456789",
+            "#ggg",
+            "+fff",
+            "# fff",
+        ] {
+            assert_eq!(parse(bad), Err(ColorParseError), "{:?}", bad);
+        }
+    }
+
+    #[test]
+    fn srgb_converts_to_linear_and_back() {
+        assert_eq!(Color::WHITE.to_linear(), Color::WHITE);
+        assert_eq!(Color::BLACK.to_srgb(), Color::BLACK);
+        // sRGB 0.5 is about 0.214 in linear light
+        let gray = Color::rgba(0.5, 0.5, 0.5, 0.5).to_linear();
+        assert_eq!(gray, Color::rgba8(55, 55, 55, 128));
+        assert_eq!(
+            Color::rgba8(55, 55, 55, 128).to_srgb(),
+            Color::rgba8(128, 128, 128, 128)
+        );
+        for i in 0..=100 {
+            let value = i as f32 / 100.0;
+            assert!((srgb_to_linear(linear_to_srgb(value)) - value).abs() < 1e-5);
+        }
+    }
+}
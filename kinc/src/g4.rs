@@ -7,6 +7,7 @@ bitflags::bitflags! {
     }
 }
 
+use alloc::{vec, vec::Vec};
 use core::{
     cell::UnsafeCell,
     ffi::{c_void, CStr},
@@ -14,7 +15,12 @@ use core::{
     ops::{Deref, DerefMut},
 };
 
-use crate::{sys::*, GetRaw, Window};
+use crate::{
+    image::{Image, ImageFormat},
+    math::{Mat3, Mat4, Vec2, Vec3, Vec4},
+    sys::*,
+    Color, GetRaw, Window,
+};
 
 pub struct RenderPass<'a> {
     window: &'a Window,
@@ -62,9 +68,104 @@ impl<'a> RenderPass<'a> {
         unsafe { kinc_g4_draw_indexed_vertices_from_to_from(start, count, vertex_offset) }
     }
 
-    pub fn clear(&mut self, flags: ClearMode, color: u32, depth: f32, stencil: i32) {
+    pub fn clear(&mut self, flags: ClearMode, color: Color, depth: f32, stencil: i32) {
+        unsafe {
+            kinc_g4_clear(flags.bits(), color.into(), depth, stencil);
+        }
+    }
+
+    /// Renders into one face of a cube render target.
+    ///
+    /// # Panics
+    /// If the render target is not a cube map.
+    pub fn set_render_target_face(&mut self, render_target: &RenderTarget, face: CubeFace) {
+        assert!(
+            render_target.is_cube_map(),
+            "render target is not a cube map"
+        );
+        // Safety: render_target is a valid cube render target
+        unsafe { kinc_g4_set_render_target_face(render_target.get_raw(), face as i32) }
+    }
+
+    pub fn set_texture(&mut self, unit: TextureUnit, texture: &Texture) {
+        // Safety: unit was retrieved from a pipeline, texture is a valid texture
+        unsafe { kinc_g4_set_texture(unit.unit, texture.get_raw()) }
+    }
+
+    pub fn set_texture_array(&mut self, unit: TextureUnit, array: &TextureArray) {
+        // Safety: unit was retrieved from a pipeline, array is a valid texture array
+        unsafe { kinc_g4_set_texture_array(unit.unit, array.get_raw()) }
+    }
+
+    /// Samples the color attachment of a render target, which can be a cube map.
+    pub fn set_render_target_texture(&mut self, unit: TextureUnit, render_target: &RenderTarget) {
+        // Safety: unit was retrieved from a pipeline, render_target is a valid render target
+        unsafe { kinc_g4_render_target_use_color_as_texture(render_target.get_raw(), unit.unit) }
+    }
+
+    /// Binds a texture, texture array or render target, whichever `texture` is.
+    pub fn set_sampled(&mut self, unit: TextureUnit, texture: &impl Sampled) {
+        texture.bind(self, unit)
+    }
+
+    pub fn set_int(&mut self, location: ConstantLocation, value: i32) {
+        // Safety: location was retrieved from a pipeline
+        unsafe { kinc_g4_set_int(location.location, value) }
+    }
+
+    pub fn set_bool(&mut self, location: ConstantLocation, value: bool) {
+        // Safety: location was retrieved from a pipeline
+        unsafe { kinc_g4_set_bool(location.location, value) }
+    }
+
+    pub fn set_float(&mut self, location: ConstantLocation, value: f32) {
+        // Safety: location was retrieved from a pipeline
+        unsafe { kinc_g4_set_float(location.location, value) }
+    }
+
+    pub fn set_float2(&mut self, location: ConstantLocation, value: Vec2) {
+        // Safety: location was retrieved from a pipeline
+        unsafe { kinc_g4_set_float2(location.location, value.x, value.y) }
+    }
+
+    pub fn set_float3(&mut self, location: ConstantLocation, value: Vec3) {
+        // Safety: location was retrieved from a pipeline
+        unsafe { kinc_g4_set_float3(location.location, value.x, value.y, value.z) }
+    }
+
+    pub fn set_float4(&mut self, location: ConstantLocation, value: Vec4) {
+        // Safety: location was retrieved from a pipeline
+        unsafe { kinc_g4_set_float4(location.location, value.x, value.y, value.z, value.w) }
+    }
+
+    pub fn set_floats(&mut self, location: ConstantLocation, values: &[f32]) {
+        // Safety: location was retrieved from a pipeline, Kinc only reads from values
+        unsafe {
+            kinc_g4_set_floats(
+                location.location,
+                values.as_ptr().cast_mut(),
+                values.len().try_into().unwrap(),
+            )
+        }
+    }
+
+    pub fn set_matrix3(&mut self, location: ConstantLocation, value: &Mat3) {
+        // Safety: location was retrieved from a pipeline, Kinc only reads from the matrix
+        unsafe {
+            kinc_g4_set_matrix3(
+                location.location,
+                (value.as_ref() as *const kinc_matrix3x3_t).cast_mut(),
+            )
+        }
+    }
+
+    pub fn set_matrix4(&mut self, location: ConstantLocation, value: &Mat4) {
+        // Safety: location was retrieved from a pipeline, Kinc only reads from the matrix
         unsafe {
-            kinc_g4_clear(flags.bits(), color, depth, stencil);
+            kinc_g4_set_matrix4(
+                location.location,
+                (value.as_ref() as *const kinc_matrix4x4_t).cast_mut(),
+            )
         }
     }
 
@@ -160,6 +261,54 @@ pub enum VertexData {
 }
 
 impl VertexData {
+    const ALL: [Self; 38] = [
+        Self::None,
+        Self::F32_1X,
+        Self::F32_2X,
+        Self::F32_3X,
+        Self::F32_4X,
+        Self::F32_4X4,
+        Self::I8_1X,
+        Self::U8_1X,
+        Self::I8_1xNormalized,
+        Self::U8_1xNormalized,
+        Self::I8_2X,
+        Self::U8_2X,
+        Self::I8_2xNormalized,
+        Self::U8_2xNormalized,
+        Self::I8_4X,
+        Self::U8_4X,
+        Self::I8_4xNormalized,
+        Self::U8_4xNormalized,
+        Self::I16_1X,
+        Self::U16_1X,
+        Self::I16_1xNormalized,
+        Self::U16_1xNormalized,
+        Self::I16_2X,
+        Self::U16_2X,
+        Self::I16_2xNormalized,
+        Self::U16_2xNormalized,
+        Self::I16_4X,
+        Self::U16_4X,
+        Self::I16_4xNormalized,
+        Self::U16_4xNormalized,
+        Self::I32_1X,
+        Self::U32_1X,
+        Self::I32_2X,
+        Self::U32_2X,
+        Self::I32_3X,
+        Self::U32_3X,
+        Self::I32_4X,
+        Self::U32_4X,
+    ];
+
+    fn from_raw(data: kinc_g4_vertex_data_t) -> Self {
+        Self::ALL
+            .into_iter()
+            .find(|&candidate| Into::<kinc_g4_vertex_data_t>::into(candidate) == data)
+            .unwrap_or(Self::None)
+    }
+
     pub fn size(&self) -> i32 {
         match self {
             Self::None => 0,
@@ -287,6 +436,19 @@ impl<'a> VertexStructure<'a> {
     pub fn size(&self) -> i32 {
         unsafe { (*self.vertex_structure.get()).size }
     }
+
+    /// The names and data types of the elements in the order they were added.
+    pub fn elements(&self) -> impl Iterator<Item = (&'a CStr, VertexData)> + '_ {
+        // Safety: the structure is initialized, and `size` counts the added elements
+        let structure = unsafe { &*self.vertex_structure.get() };
+        structure.elements[..structure.size as usize]
+            .iter()
+            .map(|element| {
+                // Safety: the names were added as `&'a CStr` by the builder
+                let name = unsafe { CStr::from_ptr(element.name) };
+                (name, VertexData::from_raw(element.data))
+            })
+    }
 }
 
 impl Clone for VertexStructure<'_> {
@@ -523,16 +685,135 @@ impl Drop for IndexBuffer {
     }
 }
 
+#[repr(transparent)]
 pub struct Texture {
     texture: UnsafeCell<kinc_g4_texture>,
 }
 
+impl Texture {
+    pub fn new(width: i32, height: i32, format: ImageFormat) -> Self {
+        // Safety: usage of zeroed() + the kinc init function should be sufficient to initialize the texture
+        unsafe {
+            let mut texture = MaybeUninit::zeroed();
+            kinc_g4_texture_init(texture.as_mut_ptr(), width, height, format.into());
+            Self {
+                texture: UnsafeCell::new(texture.assume_init()),
+            }
+        }
+    }
+
+    pub fn new3d(width: i32, height: i32, depth: i32, format: ImageFormat) -> Self {
+        // Safety: usage of zeroed() + the kinc init function should be sufficient to initialize the texture
+        unsafe {
+            let mut texture = MaybeUninit::zeroed();
+            kinc_g4_texture_init3d(texture.as_mut_ptr(), width, height, depth, format.into());
+            Self {
+                texture: UnsafeCell::new(texture.assume_init()),
+            }
+        }
+    }
+
+    pub fn from_image(image: &Image) -> Self {
+        // Safety: image is a valid image, kinc_g4_texture_init_from_image copies its pixels
+        unsafe {
+            let mut texture = MaybeUninit::zeroed();
+            kinc_g4_texture_init_from_image(texture.as_mut_ptr(), image.get_raw());
+            Self {
+                texture: UnsafeCell::new(texture.assume_init()),
+            }
+        }
+    }
+
+    /// Borrows a texture owned by Kinc, such as the frame of a video.
+    ///
+    /// # Safety
+    /// `texture` has to point to an initialized texture that stays alive and is not destroyed for `'a`.
+    pub(crate) unsafe fn from_raw<'a>(texture: *mut kinc_g4_texture) -> &'a Texture {
+        // Safety: Texture is #[repr(transparent)] over an UnsafeCell of the raw texture
+        unsafe { &*texture.cast::<Texture>() }
+    }
+
+    pub fn width(&self) -> i32 {
+        unsafe { (*self.get_raw()).tex_width }
+    }
+
+    pub fn height(&self) -> i32 {
+        unsafe { (*self.get_raw()).tex_height }
+    }
+
+    /// The depth of a 3D texture, 1 for other textures.
+    pub fn depth(&self) -> i32 {
+        unsafe { (*self.get_raw()).tex_depth }
+    }
+}
+
+impl From<&Image> for Texture {
+    fn from(image: &Image) -> Self {
+        Self::from_image(image)
+    }
+}
+
 impl GetRaw<kinc_g4_texture> for Texture {
     fn get_raw(&self) -> *mut kinc_g4_texture {
         self.texture.get()
     }
 }
 
+impl Drop for Texture {
+    fn drop(&mut self) {
+        // Safety: self.get_raw is a valid pointer to an initialized texture
+        unsafe { kinc_g4_texture_destroy(self.get_raw()) }
+    }
+}
+
+/// Textures of the same size and format, sampled as a single texture with a layer index.
+pub struct TextureArray {
+    array: UnsafeCell<kinc_g4_texture_array>,
+}
+
+impl TextureArray {
+    /// # Panics
+    /// If `images` is empty.
+    pub fn new(images: &[Image]) -> Self {
+        assert!(
+            !images.is_empty(),
+            "a texture array needs at least one image"
+        );
+        // Kinc expects the images next to each other, the copies share the pixels of `images`
+        // Safety: every image is initialized
+        let mut raw: Vec<kinc_image_t> = images
+            .iter()
+            .map(|image| unsafe { *image.get_raw() })
+            .collect();
+        // Safety: usage of zeroed() + the kinc init function should be sufficient to initialize the array,
+        // raw is only read during the call, which copies the pixels
+        unsafe {
+            let mut array = MaybeUninit::zeroed();
+            kinc_g4_texture_array_init(
+                array.as_mut_ptr(),
+                raw.as_mut_ptr(),
+                raw.len().try_into().unwrap(),
+            );
+            Self {
+                array: UnsafeCell::new(array.assume_init()),
+            }
+        }
+    }
+}
+
+impl GetRaw<kinc_g4_texture_array> for TextureArray {
+    fn get_raw(&self) -> *mut kinc_g4_texture_array {
+        self.array.get()
+    }
+}
+
+impl Drop for TextureArray {
+    fn drop(&mut self) {
+        // Safety: self.get_raw is a valid pointer to an initialized texture array
+        unsafe { kinc_g4_texture_array_destroy(self.get_raw()) }
+    }
+}
+
 #[derive(Debug, Copy, Clone)]
 pub enum ShaderType {
     Vertex,
@@ -604,6 +885,21 @@ pub enum RenderTargetFormat {
     F16Red,
 }
 
+impl RenderTargetFormat {
+    /// The image format of pixels read back from a render target with this format.
+    pub fn image_format(self) -> Option<ImageFormat> {
+        match self {
+            RenderTargetFormat::I32 => Some(ImageFormat::RGBA32),
+            RenderTargetFormat::F64 => Some(ImageFormat::RGBA64),
+            RenderTargetFormat::I32Red => Some(ImageFormat::A32),
+            RenderTargetFormat::F128 => Some(ImageFormat::RGBA128),
+            RenderTargetFormat::I16Depth => None,
+            RenderTargetFormat::I8Red => Some(ImageFormat::Grey8),
+            RenderTargetFormat::F16Red => Some(ImageFormat::A16),
+        }
+    }
+}
+
 impl Into<kinc_g4_render_target_format_t> for RenderTargetFormat {
     fn into(self) -> kinc_g4_render_target_format_t {
         match self {
@@ -760,10 +1056,61 @@ impl StencilAction {
         }
     }
 }
+
+/// The location of a uniform in a [`Pipeline`], used with the setters of [`RenderPass`].
+#[derive(Copy, Clone)]
+pub struct ConstantLocation {
+    location: kinc_g4_constant_location_t,
+}
+
+/// A texture slot in a [`Pipeline`], used with the texture setters of [`RenderPass`].
+#[derive(Copy, Clone)]
+pub struct TextureUnit {
+    unit: kinc_g4_texture_unit_t,
+}
+
+/// Something shaders can sample, see [`RenderPass::set_sampled`].
+pub trait Sampled {
+    fn bind(&self, pass: &mut RenderPass<'_>, unit: TextureUnit);
+}
+
+impl Sampled for Texture {
+    fn bind(&self, pass: &mut RenderPass<'_>, unit: TextureUnit) {
+        pass.set_texture(unit, self)
+    }
+}
+
+impl Sampled for TextureArray {
+    fn bind(&self, pass: &mut RenderPass<'_>, unit: TextureUnit) {
+        pass.set_texture_array(unit, self)
+    }
+}
+
+impl Sampled for RenderTarget {
+    fn bind(&self, pass: &mut RenderPass<'_>, unit: TextureUnit) {
+        pass.set_render_target_texture(unit, self)
+    }
+}
+
 pub struct Pipeline {
     pipeline: UnsafeCell<kinc_g4_pipeline>,
 }
 
+impl Pipeline {
+    pub fn get_constant_location(&self, name: &CStr) -> ConstantLocation {
+        // Safety: the pipeline is compiled, name is a valid C string
+        let location =
+            unsafe { kinc_g4_pipeline_get_constant_location(self.get_raw(), name.as_ptr()) };
+        ConstantLocation { location }
+    }
+
+    pub fn get_texture_unit(&self, name: &CStr) -> TextureUnit {
+        // Safety: the pipeline is compiled, name is a valid C string
+        let unit = unsafe { kinc_g4_pipeline_get_texture_unit(self.get_raw(), name.as_ptr()) };
+        TextureUnit { unit }
+    }
+}
+
 impl GetRaw<kinc_g4_pipeline> for Pipeline {
     fn get_raw(&self) -> *mut kinc_g4_pipeline {
         self.pipeline.get()
@@ -1035,10 +1382,120 @@ impl<'a> PipelineBuilder<'a> {
     }
 }
 
+// `target` has to stay the first field, since `RenderPass::set_render_targets`
+// passes `&RenderTarget`s to Kinc as `kinc_g4_render_target` pointers.
 #[derive(Debug)]
-#[repr(transparent)]
+#[repr(C)]
 pub struct RenderTarget {
     target: UnsafeCell<kinc_g4_render_target>,
+    format: RenderTargetFormat,
+    cube: bool,
+}
+
+/// The faces of a cube map, in the order Kinc and the graphics APIs use.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CubeFace {
+    PositiveX,
+    NegativeX,
+    PositiveY,
+    NegativeY,
+    PositiveZ,
+    NegativeZ,
+}
+
+impl CubeFace {
+    pub const ALL: [CubeFace; 6] = [
+        CubeFace::PositiveX,
+        CubeFace::NegativeX,
+        CubeFace::PositiveY,
+        CubeFace::NegativeY,
+        CubeFace::PositiveZ,
+        CubeFace::NegativeZ,
+    ];
+}
+
+impl RenderTarget {
+    pub fn new(
+        width: i32,
+        height: i32,
+        format: RenderTargetFormat,
+        depth_buffer_bits: i32,
+        stencil_buffer_bits: i32,
+    ) -> Self {
+        // Safety: usage of zeroed() + the kinc init function should be sufficient to initialize the render target
+        unsafe {
+            let mut target = MaybeUninit::zeroed();
+            kinc_g4_render_target_init(
+                target.as_mut_ptr(),
+                width,
+                height,
+                format.into(),
+                depth_buffer_bits,
+                stencil_buffer_bits,
+            );
+            Self {
+                target: UnsafeCell::new(target.assume_init()),
+                format,
+                cube: false,
+            }
+        }
+    }
+
+    /// Creates a cube map with six square faces, see [`RenderPass::set_render_target_face`].
+    pub fn new_cube(
+        size: i32,
+        format: RenderTargetFormat,
+        depth_buffer_bits: i32,
+        stencil_buffer_bits: i32,
+    ) -> Self {
+        // Safety: usage of zeroed() + the kinc init function should be sufficient to initialize the render target
+        unsafe {
+            let mut target = MaybeUninit::zeroed();
+            kinc_g4_render_target_init_cube(
+                target.as_mut_ptr(),
+                size,
+                format.into(),
+                depth_buffer_bits,
+                stencil_buffer_bits,
+            );
+            Self {
+                target: UnsafeCell::new(target.assume_init()),
+                format,
+                cube: true,
+            }
+        }
+    }
+
+    pub fn is_cube_map(&self) -> bool {
+        self.cube
+    }
+
+    pub fn width(&self) -> i32 {
+        unsafe { (*self.get_raw()).width }
+    }
+
+    pub fn height(&self) -> i32 {
+        unsafe { (*self.get_raw()).height }
+    }
+
+    pub fn format(&self) -> RenderTargetFormat {
+        self.format
+    }
+
+    /// Reads the color attachment back from the GPU.
+    ///
+    /// # Panics
+    /// If the render target is a depth target.
+    pub fn read_pixels(&self) -> Image {
+        let format = self
+            .format
+            .image_format()
+            .expect("can't read back the pixels of a depth render target");
+        let mut pixels = vec![0_u8; self.width() as usize * self.height() as usize * format.size()];
+        // Safety: pixels is large enough to hold the whole render target
+        unsafe { kinc_g4_render_target_get_pixels(self.get_raw(), pixels.as_mut_ptr()) }
+        Image::from_bytes(pixels, self.width(), self.height(), format)
+    }
 }
 
 impl GetRaw<kinc_g4_render_target> for RenderTarget {
@@ -1064,3 +1521,41 @@ impl core::fmt::Display for SwapBufferError {
 
 #[cfg(std)]
 impl std::error::Error for SwapBufferError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    crate::krafix_shader_interface!(
+        vertex,
+        spirv,
+        "#version 450
+layout(location = 0) in float weight;
+layout(location = 1) in vec3 pos;
+layout(location = 2) in vec2 uv;
+layout(location = 3) in ivec3 bones;
+layout(location = 4) in vec4 color;
+layout(location = 5) in uint flags;
+layout(location = 6) in mat4 model;
+layout(location = 10) in uvec2 ids;
+void main() {
+    gl_Position = model * vec4(pos * weight, 1.0) * color + vec4(uv, vec2(bones.xy + ivec2(ids) + int(flags)));
+}
+",
+        krate = crate,
+        vertex = Vertex,
+    );
+
+    #[test]
+    fn generated_vertex_matches_its_structure() {
+        let structure = Vertex::structure();
+        let stride: i32 = structure.elements().map(|(_, data)| data.size()).sum();
+        assert_eq!(core::mem::size_of::<Vertex>(), stride as usize);
+
+        let names: Vec<_> = structure.elements().map(|(name, _)| name).collect();
+        assert_eq!(
+            names,
+            [c"weight", c"pos", c"uv", c"bones", c"color", c"flags", c"model", c"ids"]
+        );
+    }
+}
@@ -0,0 +1,346 @@
+//! A fixed-timestep loop on top of [`Callbacks::update`].
+//!
+//! [`FixedStep`] holds the accumulator and works with any [`Clock`],
+//! [`GameLoop`] wraps any [`Callbacks`] and drives their [`Callbacks::fixed_update`]
+//! and [`Callbacks::render`] with it once per Kinc frame.
+
+use core::time::Duration;
+
+use crate::{system, Callbacks, Kinc};
+
+/// A monotonic time source.
+pub trait Clock {
+    /// The time since some fixed point in the past.
+    fn now(&mut self) -> Duration;
+}
+
+/// Kinc's high-resolution timer.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct KincClock;
+
+impl Clock for KincClock {
+    fn now(&mut self) -> Duration {
+        system::time()
+    }
+}
+
+/// What to do in a frame, as computed by [`FixedStep::advance`].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Frame {
+    /// How many fixed updates to run.
+    pub steps: u32,
+    /// How far the remaining time is into the next step, between 0 and 1, for interpolating when rendering.
+    pub alpha: f32,
+}
+
+#[derive(Copy, Clone, Debug, Default)]
+pub struct FrameStats {
+    /// The time between the last two frames.
+    pub frame_time: Duration,
+    /// An exponential moving average of the frame time.
+    pub average_frame_time: Duration,
+    pub min_frame_time: Duration,
+    pub max_frame_time: Duration,
+    pub frames: u64,
+    pub fixed_updates: u64,
+    /// The total time skipped because a frame would have needed more than the maximum number of steps.
+    pub dropped_time: Duration,
+}
+
+impl FrameStats {
+    /// Frames per second, based on the average frame time.
+    pub fn fps(&self) -> f32 {
+        let seconds = self.average_frame_time.as_secs_f32();
+        if seconds > 0.0 {
+            1.0 / seconds
+        } else {
+            0.0
+        }
+    }
+
+    fn record(&mut self, frame_time: Duration) {
+        if self.frames == 0 {
+            self.average_frame_time = frame_time;
+            self.min_frame_time = frame_time;
+            self.max_frame_time = frame_time;
+        } else {
+            // Weighs the newest frame by 1/10
+            self.average_frame_time = (self.average_frame_time * 9 + frame_time) / 10;
+            self.min_frame_time = self.min_frame_time.min(frame_time);
+            self.max_frame_time = self.max_frame_time.max(frame_time);
+        }
+        self.frame_time = frame_time;
+        self.frames += 1;
+    }
+}
+
+/// Converts the time between frames into a number of fixed steps.
+pub struct FixedStep<C: Clock = KincClock> {
+    clock: C,
+    step: Duration,
+    max_steps: u32,
+    accumulator: Duration,
+    last: Option<Duration>,
+    stats: FrameStats,
+}
+
+impl FixedStep<KincClock> {
+    /// # Panics
+    /// Panics if `step` is zero.
+    pub fn new(step: Duration) -> Self {
+        Self::with_clock(step, KincClock)
+    }
+}
+
+impl<C: Clock> FixedStep<C> {
+    /// # Panics
+    /// Panics if `step` is zero.
+    pub fn with_clock(step: Duration, clock: C) -> Self {
+        assert!(!step.is_zero(), "the fixed step must not be zero");
+        Self {
+            clock,
+            step,
+            max_steps: 8,
+            accumulator: Duration::ZERO,
+            last: None,
+            stats: FrameStats::default(),
+        }
+    }
+
+    /// Limits the number of steps per frame, the time that would need more steps is dropped,
+    /// so that a slow frame can't cause ever slower frames. Defaults to 8.
+    pub fn max_steps(mut self, max_steps: u32) -> Self {
+        self.max_steps = max_steps.max(1);
+        self
+    }
+
+    pub fn step(&self) -> Duration {
+        self.step
+    }
+
+    pub fn stats(&self) -> &FrameStats {
+        &self.stats
+    }
+
+    pub fn clock_mut(&mut self) -> &mut C {
+        &mut self.clock
+    }
+
+    /// Reads the clock and consumes the elapsed time in whole steps.
+    /// The first call only starts the clock and returns no steps.
+    pub fn advance(&mut self) -> Frame {
+        let now = self.clock.now();
+        let Some(last) = self.last.replace(now) else {
+            return Frame {
+                steps: 0,
+                alpha: 0.0,
+            };
+        };
+        let frame_time = now.saturating_sub(last);
+        self.stats.record(frame_time);
+
+        self.accumulator += frame_time;
+        let max = self.step * self.max_steps;
+        if self.accumulator > max {
+            self.stats.dropped_time += self.accumulator - max;
+            self.accumulator = max;
+        }
+
+        let mut steps = 0;
+        while self.accumulator >= self.step {
+            self.accumulator -= self.step;
+            steps += 1;
+        }
+        self.stats.fixed_updates += u64::from(steps);
+
+        Frame {
+            steps,
+            alpha: self.accumulator.as_secs_f32() / self.step.as_secs_f32(),
+        }
+    }
+}
+
+/// Runs the fixed updates of some [`Callbacks`] at a fixed rate, pass it to [`Kinc::start`].
+pub struct GameLoop<T, C: Clock = KincClock> {
+    callbacks: T,
+    step: FixedStep<C>,
+}
+
+impl<T: Callbacks> GameLoop<T> {
+    /// Runs `fixed_update` `rate` times per second.
+    ///
+    /// # Panics
+    /// Panics if `rate` is zero.
+    pub fn new(callbacks: T, rate: u32) -> Self {
+        assert!(rate > 0, "the update rate must not be zero");
+        Self::with_step(callbacks, FixedStep::new(Duration::from_secs(1) / rate))
+    }
+}
+
+impl<T: Callbacks, C: Clock> GameLoop<T, C> {
+    pub fn with_step(callbacks: T, step: FixedStep<C>) -> Self {
+        Self { callbacks, step }
+    }
+
+    pub fn stats(&self) -> &FrameStats {
+        self.step.stats()
+    }
+
+    pub fn callbacks(&self) -> &T {
+        &self.callbacks
+    }
+
+    pub fn callbacks_mut(&mut self) -> &mut T {
+        &mut self.callbacks
+    }
+}
+
+impl<T: Callbacks, C: Clock> Callbacks for GameLoop<T, C> {
+    fn update(&mut self, kinc: &mut Kinc) {
+        let frame = self.step.advance();
+        let dt = self.step.step();
+        for _ in 0..frame.steps {
+            self.callbacks.fixed_update(kinc, dt);
+        }
+        self.callbacks.render(kinc, frame.alpha);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::rc::Rc;
+    use core::cell::Cell;
+
+    /// A clock that only moves when the test says so.
+    #[derive(Clone, Default)]
+    struct FakeClock(Rc<Cell<Duration>>);
+
+    impl FakeClock {
+        fn advance(&self, by: Duration) {
+            self.0.set(self.0.get() + by);
+        }
+    }
+
+    impl Clock for FakeClock {
+        fn now(&mut self) -> Duration {
+            self.0.get()
+        }
+    }
+
+    fn ms(ms: u64) -> Duration {
+        Duration::from_millis(ms)
+    }
+
+    #[test]
+    fn accumulates_whole_steps() {
+        let clock = FakeClock::default();
+        let mut step = FixedStep::with_clock(ms(10), clock.clone());
+        assert_eq!(step.advance().steps, 0);
+
+        clock.advance(ms(25));
+        let frame = step.advance();
+        assert_eq!(frame.steps, 2);
+        assert!((frame.alpha - 0.5).abs() < 1e-6);
+
+        // The remaining 5ms carry over
+        clock.advance(ms(5));
+        assert_eq!(step.advance().steps, 1);
+        clock.advance(ms(9));
+        assert_eq!(step.advance().steps, 0);
+        assert_eq!(step.stats().fixed_updates, 3);
+        assert_eq!(step.stats().frames, 3);
+    }
+
+    #[test]
+    fn caps_the_steps_per_frame() {
+        let clock = FakeClock::default();
+        let mut step = FixedStep::with_clock(ms(10), clock.clone()).max_steps(3);
+        step.advance();
+
+        clock.advance(ms(100));
+        let frame = step.advance();
+        assert_eq!(frame.steps, 3);
+        assert_eq!(frame.alpha, 0.0);
+        assert_eq!(step.stats().dropped_time, ms(70));
+
+        // Nothing of the dropped time is caught up later
+        clock.advance(ms(10));
+        assert_eq!(step.advance().steps, 1);
+    }
+
+    #[test]
+    fn records_frame_times() {
+        let clock = FakeClock::default();
+        let mut step = FixedStep::with_clock(ms(10), clock.clone());
+        step.advance();
+        for frame_time in [20, 10, 30] {
+            clock.advance(ms(frame_time));
+            step.advance();
+        }
+        let stats = step.stats();
+        assert_eq!(stats.frame_time, ms(30));
+        assert_eq!(stats.min_frame_time, ms(10));
+        assert_eq!(stats.max_frame_time, ms(30));
+        assert_eq!(stats.frames, 3);
+        assert!(stats.fps() > 0.0);
+    }
+
+    #[derive(Default)]
+    struct Recorder {
+        fixed_updates: Vec<Duration>,
+        renders: Vec<f32>,
+    }
+
+    impl Callbacks for Recorder {
+        fn fixed_update(&mut self, _kinc: &mut Kinc, dt: Duration) {
+            self.fixed_updates.push(dt);
+        }
+
+        fn render(&mut self, _kinc: &mut Kinc, alpha: f32) {
+            self.renders.push(alpha);
+        }
+    }
+
+    #[derive(Default)]
+    struct UpdateOnly {
+        updates: u32,
+    }
+
+    impl Callbacks for UpdateOnly {
+        fn update(&mut self, _kinc: &mut Kinc) {
+            self.updates += 1;
+        }
+    }
+
+    #[test]
+    fn game_loop_drives_the_wrapped_callbacks() {
+        let clock = FakeClock::default();
+        let mut game_loop = GameLoop::with_step(
+            Recorder::default(),
+            FixedStep::with_clock(ms(10), clock.clone()),
+        );
+        game_loop.update(&mut Kinc);
+        clock.advance(ms(35));
+        game_loop.update(&mut Kinc);
+
+        let recorder = game_loop.callbacks();
+        assert_eq!(recorder.fixed_updates, [ms(10); 3]);
+        assert_eq!(recorder.renders.len(), 2);
+        assert!((recorder.renders[1] - 0.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn plain_callbacks_keep_updating_once_per_frame() {
+        let clock = FakeClock::default();
+        let mut game_loop = GameLoop::with_step(
+            UpdateOnly::default(),
+            FixedStep::with_clock(ms(10), clock.clone()),
+        );
+        for _ in 0..4 {
+            clock.advance(ms(25));
+            game_loop.update(&mut Kinc);
+        }
+        assert_eq!(game_loop.callbacks().updates, 4);
+    }
+}
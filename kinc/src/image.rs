@@ -0,0 +1,585 @@
+use alloc::{vec, vec::Vec};
+use core::{
+    cell::UnsafeCell,
+    ffi::{c_void, CStr},
+    mem::MaybeUninit,
+};
+
+use crate::{sys::*, GetRaw};
+
+mod png;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ImageFormat {
+    RGBA32,
+    Grey8,
+    RGB24,
+    RGBA128,
+    RGBA64,
+    A32,
+    BGRA32,
+    A16,
+}
+
+impl ImageFormat {
+    /// The size of a single pixel in bytes.
+    pub fn size(&self) -> usize {
+        // Safety: kinc_image_format_sizeof only reads its argument
+        unsafe { kinc_image_format_sizeof((*self).into()) as usize }
+    }
+
+    fn from_raw(format: kinc_image_format_t) -> Option<Self> {
+        #[allow(non_upper_case_globals)]
+        Some(match format {
+            kinc_image_format_KINC_IMAGE_FORMAT_RGBA32 => Self::RGBA32,
+            kinc_image_format_KINC_IMAGE_FORMAT_GREY8 => Self::Grey8,
+            kinc_image_format_KINC_IMAGE_FORMAT_RGB24 => Self::RGB24,
+            kinc_image_format_KINC_IMAGE_FORMAT_RGBA128 => Self::RGBA128,
+            kinc_image_format_KINC_IMAGE_FORMAT_RGBA64 => Self::RGBA64,
+            kinc_image_format_KINC_IMAGE_FORMAT_A32 => Self::A32,
+            kinc_image_format_KINC_IMAGE_FORMAT_BGRA32 => Self::BGRA32,
+            kinc_image_format_KINC_IMAGE_FORMAT_A16 => Self::A16,
+            _ => return None,
+        })
+    }
+}
+
+impl Into<kinc_image_format_t> for ImageFormat {
+    fn into(self) -> kinc_image_format_t {
+        match self {
+            Self::RGBA32 => kinc_image_format_KINC_IMAGE_FORMAT_RGBA32,
+            Self::Grey8 => kinc_image_format_KINC_IMAGE_FORMAT_GREY8,
+            Self::RGB24 => kinc_image_format_KINC_IMAGE_FORMAT_RGB24,
+            Self::RGBA128 => kinc_image_format_KINC_IMAGE_FORMAT_RGBA128,
+            Self::RGBA64 => kinc_image_format_KINC_IMAGE_FORMAT_RGBA64,
+            Self::A32 => kinc_image_format_KINC_IMAGE_FORMAT_A32,
+            Self::BGRA32 => kinc_image_format_KINC_IMAGE_FORMAT_BGRA32,
+            Self::A16 => kinc_image_format_KINC_IMAGE_FORMAT_A16,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ImageCompression {
+    None,
+    DXT5,
+    ASTC,
+    PVRTC,
+}
+
+impl ImageCompression {
+    fn from_raw(compression: kinc_image_compression_t) -> Self {
+        #[allow(non_upper_case_globals)]
+        match compression {
+            kinc_image_compression_KINC_IMAGE_COMPRESSION_DXT5 => Self::DXT5,
+            kinc_image_compression_KINC_IMAGE_COMPRESSION_ASTC => Self::ASTC,
+            kinc_image_compression_KINC_IMAGE_COMPRESSION_PVRTC => Self::PVRTC,
+            _ => Self::None,
+        }
+    }
+}
+
+/// Format string for Kinc's LZ4X-compressed `.k` images.
+pub const KINC_IMAGE_FORMAT: &CStr = c"k";
+pub const PNG_FORMAT: &CStr = c"png";
+pub const JPEG_FORMAT: &CStr = c"jpg";
+pub const HDR_FORMAT: &CStr = c"hdr";
+
+/// A source of encoded image data that Kinc can pull from while decoding.
+pub trait ImageReader {
+    /// Reads up to `buf.len()` bytes and returns how many were read.
+    fn read(&mut self, buf: &mut [u8]) -> usize;
+    fn seek(&mut self, pos: usize);
+    fn pos(&self) -> usize;
+    fn size(&self) -> usize;
+}
+
+/// An [`ImageReader`] over an in-memory buffer.
+pub struct SliceReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> SliceReader<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+}
+
+impl ImageReader for SliceReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> usize {
+        let remaining = &self.data[self.pos..];
+        let count = remaining.len().min(buf.len());
+        buf[..count].copy_from_slice(&remaining[..count]);
+        self.pos += count;
+        count
+    }
+
+    fn seek(&mut self, pos: usize) {
+        self.pos = pos.min(self.data.len());
+    }
+
+    fn pos(&self) -> usize {
+        self.pos
+    }
+
+    fn size(&self) -> usize {
+        self.data.len()
+    }
+}
+
+extern "C" fn _read_cb<R: ImageReader>(
+    user_data: *mut c_void,
+    data: *mut c_void,
+    size: usize,
+) -> usize {
+    // Safety: user_data is the `&mut R` passed to kinc_image_*_from_callbacks,
+    // and data points to at least `size` writable bytes.
+    unsafe {
+        let reader = &mut *user_data.cast::<R>();
+        reader.read(core::slice::from_raw_parts_mut(data.cast(), size))
+    }
+}
+
+extern "C" fn _seek_cb<R: ImageReader>(user_data: *mut c_void, pos: usize) {
+    // Safety: see _read_cb
+    unsafe { (*user_data.cast::<R>()).seek(pos) }
+}
+
+extern "C" fn _pos_cb<R: ImageReader>(user_data: *mut c_void) -> usize {
+    // Safety: see _read_cb
+    unsafe { (*user_data.cast::<R>()).pos() }
+}
+
+extern "C" fn _size_cb<R: ImageReader>(user_data: *mut c_void) -> usize {
+    // Safety: see _read_cb
+    unsafe { (*user_data.cast::<R>()).size() }
+}
+
+fn callbacks<R: ImageReader>() -> kinc_image_read_callbacks_t {
+    kinc_image_read_callbacks_t {
+        read: Some(_read_cb::<R>),
+        seek: Some(_seek_cb::<R>),
+        pos: Some(_pos_cb::<R>),
+        size: Some(_size_cb::<R>),
+    }
+}
+
+#[derive(Debug)]
+pub struct ImageLoadError;
+
+impl core::fmt::Display for ImageLoadError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "failed to load image")
+    }
+}
+
+impl core::error::Error for ImageLoadError {}
+
+pub struct Image {
+    image: UnsafeCell<kinc_image_t>,
+    // Backing storage for the pixels, `image.data` points into it.
+    memory: Vec<u8>,
+}
+
+/// The number of bytes in `width * height` pixels of `format`.
+///
+/// # Panics
+/// If a dimension isn't positive, or the size doesn't fit in memory.
+fn byte_size(width: i32, height: i32, format: ImageFormat) -> usize {
+    assert!(
+        width > 0 && height > 0,
+        "invalid image size {}x{}, both dimensions must be positive",
+        width,
+        height
+    );
+    (width as usize)
+        .checked_mul(height as usize)
+        .and_then(|pixels| pixels.checked_mul(format.size()))
+        .expect("image size overflows usize")
+}
+
+impl Image {
+    /// Creates an empty (zeroed) image.
+    ///
+    /// # Panics
+    /// If `width` or `height` isn't positive.
+    pub fn new(width: i32, height: i32, format: ImageFormat) -> Self {
+        let mut memory = vec![0; byte_size(width, height, format)];
+        // Safety: memory is large enough for an image of the given size and format
+        let image = unsafe {
+            let mut image = MaybeUninit::zeroed();
+            kinc_image_init(
+                image.as_mut_ptr(),
+                memory.as_mut_ptr().cast(),
+                width,
+                height,
+                format.into(),
+            );
+            image.assume_init()
+        };
+        Self {
+            image: UnsafeCell::new(image),
+            memory,
+        }
+    }
+
+    /// Wraps already decoded pixel data.
+    ///
+    /// # Panics
+    /// If `width` or `height` isn't positive,
+    /// or `data` is too small to hold `width * height` pixels of the given format.
+    pub fn from_bytes(mut data: Vec<u8>, width: i32, height: i32, format: ImageFormat) -> Self {
+        assert!(data.len() >= byte_size(width, height, format));
+        // Safety: data holds enough pixels, and is moved into the image so it outlives it
+        let image = unsafe {
+            let mut image = MaybeUninit::zeroed();
+            kinc_image_init_from_bytes(
+                image.as_mut_ptr(),
+                data.as_mut_ptr().cast(),
+                width,
+                height,
+                format.into(),
+            );
+            image.assume_init()
+        };
+        Self {
+            image: UnsafeCell::new(image),
+            memory: data,
+        }
+    }
+
+    /// Loads and decodes an image file. Files ending in `.k` are decompressed with LZ4X.
+    pub fn from_file(filename: &CStr) -> Result<Self, ImageLoadError> {
+        // Safety: filename is a valid C string
+        let size = unsafe { kinc_image_size_from_file(filename.as_ptr()) };
+        if size == 0 {
+            return Err(ImageLoadError);
+        }
+        let mut memory = vec![0_u8; size];
+        // Safety: memory is as large as kinc_image_size_from_file requested
+        unsafe {
+            let mut image = MaybeUninit::zeroed();
+            if kinc_image_init_from_file(
+                image.as_mut_ptr(),
+                memory.as_mut_ptr().cast(),
+                filename.as_ptr(),
+            ) == 0
+            {
+                return Err(ImageLoadError);
+            }
+            Ok(Self {
+                image: UnsafeCell::new(image.assume_init()),
+                memory,
+            })
+        }
+    }
+
+    /// Decodes an in-memory image, `format` is the file extension (eg. [`PNG_FORMAT`]).
+    pub fn from_encoded_bytes(data: &[u8], format: &CStr) -> Result<Self, ImageLoadError> {
+        // kinc_image_*_from_encoded_bytes only reads from data.
+        let data_ptr = data.as_ptr().cast_mut().cast();
+        // Safety: data and format are valid for the duration of the call
+        let size =
+            unsafe { kinc_image_size_from_encoded_bytes(data_ptr, data.len(), format.as_ptr()) };
+        if size == 0 {
+            return Err(ImageLoadError);
+        }
+        let mut memory = vec![0_u8; size];
+        // Safety: memory is as large as kinc_image_size_from_encoded_bytes requested
+        unsafe {
+            let mut image = MaybeUninit::zeroed();
+            if kinc_image_init_from_encoded_bytes(
+                image.as_mut_ptr(),
+                memory.as_mut_ptr().cast(),
+                data_ptr,
+                data.len(),
+                format.as_ptr(),
+            ) == 0
+            {
+                return Err(ImageLoadError);
+            }
+            Ok(Self {
+                image: UnsafeCell::new(image.assume_init()),
+                memory,
+            })
+        }
+    }
+
+    /// Decodes an image pulled from `reader`, `format` is the file extension (eg. [`PNG_FORMAT`]).
+    pub fn from_reader<R: ImageReader>(
+        reader: &mut R,
+        format: &CStr,
+    ) -> Result<Self, ImageLoadError> {
+        let start = reader.pos();
+        let user_data = (reader as *mut R).cast();
+        // Safety: user_data is a valid `&mut R` for the duration of the call
+        let size =
+            unsafe { kinc_image_size_from_callbacks(callbacks::<R>(), user_data, format.as_ptr()) };
+        if size == 0 {
+            return Err(ImageLoadError);
+        }
+        reader.seek(start);
+        let mut memory = vec![0_u8; size];
+        // Safety: memory is as large as kinc_image_size_from_callbacks requested
+        unsafe {
+            let mut image = MaybeUninit::zeroed();
+            if kinc_image_init_from_callbacks(
+                image.as_mut_ptr(),
+                memory.as_mut_ptr().cast(),
+                callbacks::<R>(),
+                user_data,
+                format.as_ptr(),
+            ) == 0
+            {
+                return Err(ImageLoadError);
+            }
+            Ok(Self {
+                image: UnsafeCell::new(image.assume_init()),
+                memory,
+            })
+        }
+    }
+
+    fn raw(&self) -> &kinc_image_t {
+        // Safety: the image is only mutated through &mut self
+        unsafe { &*self.image.get() }
+    }
+
+    pub fn width(&self) -> i32 {
+        self.raw().width
+    }
+
+    pub fn height(&self) -> i32 {
+        self.raw().height
+    }
+
+    pub fn depth(&self) -> i32 {
+        self.raw().depth
+    }
+
+    /// The pixel format, `None` if Kinc reports a format this crate doesn't know yet.
+    pub fn format(&self) -> Option<ImageFormat> {
+        ImageFormat::from_raw(self.raw().format)
+    }
+
+    pub fn compression(&self) -> ImageCompression {
+        ImageCompression::from_raw(self.raw().compression)
+    }
+
+    /// The raw pixel data, which is still compressed if [`Image::compression`] isn't `None`.
+    pub fn pixels(&self) -> &[u8] {
+        let len = data_len(self.raw(), self.memory.len());
+        // Safety: data points to at least len bytes owned by the image
+        unsafe { core::slice::from_raw_parts(self.raw().data.cast(), len) }
+    }
+
+    pub fn pixels_mut(&mut self) -> &mut [u8] {
+        let len = data_len(self.image.get_mut(), self.memory.len());
+        // Safety: data points to at least len bytes owned by the image
+        unsafe { core::slice::from_raw_parts_mut(self.image.get_mut().data.cast(), len) }
+    }
+
+    /// Returns the pixel at `(x, y)`, packed as a 32 bit value.
+    ///
+    /// # Panics
+    /// If `(x, y)` is out of bounds or the image is compressed.
+    pub fn at(&self, x: i32, y: i32) -> u32 {
+        assert!(x >= 0 && x < self.width());
+        assert!(y >= 0 && y < self.height());
+        assert_eq!(self.compression(), ImageCompression::None);
+        // Safety: the coordinates are in bounds
+        unsafe { kinc_image_at(self.get_raw(), x, y) }
+    }
+
+    /// Converts the pixels of the first layer to tightly packed 8 bit RGBA.
+    ///
+    /// # Panics
+    /// If the image is compressed or its format is unknown.
+    pub fn to_rgba8(&self) -> Vec<u8> {
+        assert_eq!(self.compression(), ImageCompression::None);
+        let format = self.format().expect("unknown image format");
+        let count = self.width() as usize * self.height() as usize;
+        let pixels = &self.pixels()[..count * format.size()];
+        let mut out = Vec::with_capacity(count * 4);
+        match format {
+            ImageFormat::RGBA32 => out.extend_from_slice(pixels),
+            ImageFormat::BGRA32 => {
+                for p in pixels.chunks_exact(4) {
+                    out.extend_from_slice(&[p[2], p[1], p[0], p[3]]);
+                }
+            }
+            ImageFormat::RGB24 => {
+                for p in pixels.chunks_exact(3) {
+                    out.extend_from_slice(&[p[0], p[1], p[2], 0xff]);
+                }
+            }
+            ImageFormat::Grey8 => {
+                for p in pixels {
+                    out.extend_from_slice(&[*p, *p, *p, 0xff]);
+                }
+            }
+            ImageFormat::RGBA128 => {
+                for p in pixels.chunks_exact(4) {
+                    out.push(unorm8(f32::from_ne_bytes([p[0], p[1], p[2], p[3]])));
+                }
+            }
+            ImageFormat::RGBA64 => {
+                for p in pixels.chunks_exact(2) {
+                    out.push(unorm8(f16_to_f32(u16::from_ne_bytes([p[0], p[1]]))));
+                }
+            }
+            ImageFormat::A32 => {
+                for p in pixels.chunks_exact(4) {
+                    let a = unorm8(f32::from_ne_bytes([p[0], p[1], p[2], p[3]]));
+                    out.extend_from_slice(&[0xff, 0xff, 0xff, a]);
+                }
+            }
+            ImageFormat::A16 => {
+                for p in pixels.chunks_exact(2) {
+                    let a = unorm8(f16_to_f32(u16::from_ne_bytes([p[0], p[1]])));
+                    out.extend_from_slice(&[0xff, 0xff, 0xff, a]);
+                }
+            }
+        }
+        out
+    }
+
+    /// Encodes the first layer of the image as a PNG file.
+    ///
+    /// # Panics
+    /// If the image is compressed.
+    pub fn encode_png(&self) -> Vec<u8> {
+        png::encode_rgba8(self.width() as u32, self.height() as u32, &self.to_rgba8())
+    }
+}
+
+/// The size of the pixel data, computed from the dimensions for uncompressed images since
+/// `kinc_image_init` and `kinc_image_init_from_bytes` leave `data_size` unset.
+/// `allocated` is the size of the memory backing the image, which the result never exceeds.
+fn data_len(image: &kinc_image_t, allocated: usize) -> usize {
+    let len = if image.compression == kinc_image_compression_KINC_IMAGE_COMPRESSION_NONE {
+        // Safety: kinc_image_format_sizeof only reads its argument
+        let pixel_size = unsafe { kinc_image_format_sizeof(image.format) } as usize;
+        image.width.max(0) as usize
+            * image.height.max(0) as usize
+            * image.depth.max(1) as usize
+            * pixel_size
+    } else {
+        image.data_size
+    };
+    len.min(allocated)
+}
+
+fn unorm8(value: f32) -> u8 {
+    (value.clamp(0.0, 1.0) * 255.0 + 0.5) as u8
+}
+
+fn f16_to_f32(half: u16) -> f32 {
+    let sign = ((half & 0x8000) as u32) << 16;
+    let exponent = ((half >> 10) & 0x1f) as u32;
+    let mantissa = (half & 0x3ff) as u32;
+    let bits = match (exponent, mantissa) {
+        (0, 0) => sign,
+        (0, _) => {
+            // Subnormal, renormalize it
+            let shift = mantissa.leading_zeros() - 21;
+            sign | ((113 - shift) << 23) | ((mantissa << shift) & 0x3ff) << 13
+        }
+        (0x1f, _) => sign | 0x7f800000 | (mantissa << 13),
+        _ => sign | ((exponent + 112) << 23) | (mantissa << 13),
+    };
+    f32::from_bits(bits)
+}
+
+impl GetRaw<kinc_image_t> for Image {
+    fn get_raw(&self) -> *mut kinc_image_t {
+        self.image.get()
+    }
+}
+
+impl Drop for Image {
+    fn drop(&mut self) {
+        // Safety: self.get_raw is a valid pointer to an initialized image
+        unsafe { kinc_image_destroy(self.get_raw()) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A 2x2 RGBA PNG with a red, green, blue and half transparent white pixel.
+    const PNG: &[u8] = &[
+        0x89, 0x50, 0x4e, 0x47, 0x0d, 0x0a, 0x1a, 0x0a, 0x00, 0x00, 0x00, 0x0d, 0x49, 0x48, 0x44,
+        0x52, 0x00, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00, 0x02, 0x08, 0x06, 0x00, 0x00, 0x00, 0x72,
+        0xb6, 0x0d, 0x24, 0x00, 0x00, 0x00, 0x13, 0x49, 0x44, 0x41, 0x54, 0x78, 0x9c, 0x63, 0xf8,
+        0xcf, 0xc0, 0xf0, 0x1f, 0x0c, 0x81, 0x34, 0x08, 0x34, 0x00, 0x00, 0x49, 0x49, 0x09, 0x78,
+        0x28, 0xa0, 0xdb, 0x77, 0x00, 0x00, 0x00, 0x00, 0x49, 0x45, 0x4e, 0x44, 0xae, 0x42, 0x60,
+        0x82,
+    ];
+
+    #[test]
+    fn decodes_png_from_memory() {
+        let image = Image::from_encoded_bytes(PNG, PNG_FORMAT).unwrap();
+        assert_eq!((image.width(), image.height()), (2, 2));
+        assert_eq!(image.format(), Some(ImageFormat::RGBA32));
+        assert_eq!(image.compression(), ImageCompression::None);
+        assert_eq!(
+            image.pixels()[..12],
+            [0xff, 0, 0, 0xff, 0, 0xff, 0, 0xff, 0, 0, 0xff, 0xff]
+        );
+        assert_eq!(image.pixels()[15], 128);
+    }
+
+    #[test]
+    fn rejects_invalid_png() {
+        assert!(Image::from_encoded_bytes(&PNG[..20], PNG_FORMAT).is_err());
+    }
+
+    /// Encodes with the pure Rust PNG encoder and decodes through Kinc.
+    fn round_trip(image: &Image) -> Image {
+        Image::from_encoded_bytes(&image.encode_png(), PNG_FORMAT).unwrap()
+    }
+
+    #[test]
+    fn png_round_trip() {
+        let pixels: Vec<u8> = (0..5 * 3 * 4).map(|i| (i * 13) as u8 | 0x80).collect();
+        let image = Image::from_bytes(pixels.clone(), 5, 3, ImageFormat::RGBA32);
+        let decoded = round_trip(&image);
+        assert_eq!((decoded.width(), decoded.height()), (5, 3));
+        assert_eq!(decoded.to_rgba8(), pixels);
+    }
+
+    #[test]
+    fn png_round_trip_spanning_several_deflate_blocks() {
+        // 128 * 4 + 1 bytes per scanline, which is more than one 64 KiB stored block
+        let image = Image::from_bytes(
+            (0..128 * 200 * 3).map(|i| (i % 255) as u8).collect(),
+            128,
+            200,
+            ImageFormat::RGB24,
+        );
+        assert_eq!(round_trip(&image).to_rgba8(), image.to_rgba8());
+    }
+
+    #[test]
+    fn pixels_cover_the_whole_image() {
+        let mut image = Image::new(3, 2, ImageFormat::RGB24);
+        assert_eq!(image.pixels().len(), 3 * 2 * 3);
+        image.pixels_mut()[17] = 7;
+        assert_eq!(image.to_rgba8()[5 * 4 + 2], 7);
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid image size -1x4")]
+    fn negative_sizes_are_rejected() {
+        Image::new(-1, 4, ImageFormat::RGBA32);
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid image size 4x0")]
+    fn empty_images_are_rejected() {
+        Image::from_bytes(Vec::new(), 4, 0, ImageFormat::Grey8);
+    }
+}
@@ -0,0 +1,203 @@
+//! A minimal PNG encoder.
+//!
+//! Pixels are written as 8 bit RGBA without filtering, and the zlib stream only uses stored deflate blocks,
+//! which keeps the encoder small at the cost of larger files.
+
+use alloc::vec::Vec;
+
+const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+const MAX_STORED_BLOCK: usize = 0xffff;
+
+const CRC_TABLE: [u32; 256] = {
+    let mut table = [0; 256];
+    let mut n = 0;
+    while n < 256 {
+        let mut c = n as u32;
+        let mut k = 0;
+        while k < 8 {
+            c = if c & 1 != 0 {
+                0xedb88320 ^ (c >> 1)
+            } else {
+                c >> 1
+            };
+            k += 1;
+        }
+        table[n] = c;
+        n += 1;
+    }
+    table
+};
+
+fn crc32(chunks: &[&[u8]]) -> u32 {
+    let mut crc = 0xffffffff_u32;
+    for chunk in chunks {
+        for byte in *chunk {
+            crc = CRC_TABLE[((crc ^ *byte as u32) & 0xff) as usize] ^ (crc >> 8);
+        }
+    }
+    crc ^ 0xffffffff
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1_u32, 0_u32);
+    // 5552 is the largest n such that 255n(n+1)/2 + (n+1)(65520) fits into an u32
+    for chunk in data.chunks(5552) {
+        for byte in chunk {
+            a += *byte as u32;
+            b += a;
+        }
+        a %= 65521;
+        b %= 65521;
+    }
+    (b << 16) | a
+}
+
+fn write_chunk(out: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    out.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    out.extend_from_slice(kind);
+    out.extend_from_slice(data);
+    out.extend_from_slice(&crc32(&[kind, data]).to_be_bytes());
+}
+
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    let blocks = data.len() / MAX_STORED_BLOCK + 1;
+    let mut out = Vec::with_capacity(data.len() + blocks * 5 + 6);
+    // CMF/FLG: deflate with a 32K window, no preset dictionary, fastest compression
+    out.extend_from_slice(&[0x78, 0x01]);
+    let mut chunks = data.chunks(MAX_STORED_BLOCK).peekable();
+    if chunks.peek().is_none() {
+        out.extend_from_slice(&[1, 0, 0, 0xff, 0xff]);
+    }
+    while let Some(chunk) = chunks.next() {
+        out.push(chunks.peek().is_none() as u8);
+        let len = chunk.len() as u16;
+        out.extend_from_slice(&len.to_le_bytes());
+        out.extend_from_slice(&(!len).to_le_bytes());
+        out.extend_from_slice(chunk);
+    }
+    out.extend_from_slice(&adler32(data).to_be_bytes());
+    out
+}
+
+/// Encodes tightly packed 8 bit RGBA pixels.
+pub(super) fn encode_rgba8(width: u32, height: u32, pixels: &[u8]) -> Vec<u8> {
+    let stride = width as usize * 4;
+    assert_eq!(pixels.len(), stride * height as usize);
+
+    let mut scanlines = Vec::with_capacity((stride + 1) * height as usize);
+    for row in pixels.chunks_exact(stride.max(1)).take(height as usize) {
+        // Filter type None
+        scanlines.push(0);
+        scanlines.extend_from_slice(row);
+    }
+
+    let mut header = [0_u8; 13];
+    header[0..4].copy_from_slice(&width.to_be_bytes());
+    header[4..8].copy_from_slice(&height.to_be_bytes());
+    // Bit depth 8, color type RGBA, deflate, standard filters, no interlacing
+    header[8..13].copy_from_slice(&[8, 6, 0, 0, 0]);
+
+    let idat = zlib_stored(&scanlines);
+    let mut out = Vec::with_capacity(SIGNATURE.len() + idat.len() + 3 * 12 + header.len());
+    out.extend_from_slice(&SIGNATURE);
+    write_chunk(&mut out, b"IHDR", &header);
+    write_chunk(&mut out, b"IDAT", &idat);
+    write_chunk(&mut out, b"IEND", &[]);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Concatenates the stored blocks of a zlib stream and checks its framing.
+    fn inflate_stored(stream: &[u8]) -> Vec<u8> {
+        assert_eq!(stream[..2], [0x78, 0x01]);
+        assert_eq!(u16::from_be_bytes([stream[0], stream[1]]) % 31, 0);
+        let mut data = Vec::new();
+        let mut pos = 2;
+        loop {
+            let last = stream[pos] & 1 != 0;
+            assert_eq!(stream[pos] >> 1, 0, "only stored blocks are expected");
+            let len = u16::from_le_bytes([stream[pos + 1], stream[pos + 2]]);
+            let nlen = u16::from_le_bytes([stream[pos + 3], stream[pos + 4]]);
+            assert_eq!(len, !nlen);
+            pos += 5;
+            data.extend_from_slice(&stream[pos..pos + len as usize]);
+            pos += len as usize;
+            if last {
+                break;
+            }
+        }
+        assert_eq!(stream[pos..], adler32(&data).to_be_bytes());
+        data
+    }
+
+    /// The type and data of every chunk, checking the CRCs on the way.
+    fn chunks(png: &[u8]) -> Vec<([u8; 4], &[u8])> {
+        assert_eq!(png[..8], SIGNATURE);
+        let mut chunks = Vec::new();
+        let mut pos = 8;
+        while pos < png.len() {
+            let len = u32::from_be_bytes(png[pos..pos + 4].try_into().unwrap()) as usize;
+            let kind: [u8; 4] = png[pos + 4..pos + 8].try_into().unwrap();
+            let data = &png[pos + 8..pos + 8 + len];
+            let crc = u32::from_be_bytes(png[pos + 8 + len..pos + 12 + len].try_into().unwrap());
+            assert_eq!(crc, crc32(&[&kind, data]));
+            chunks.push((kind, data));
+            pos += 12 + len;
+        }
+        chunks
+    }
+
+    #[test]
+    fn checksums_match_reference_values() {
+        assert_eq!(crc32(&[b"123456789"]), 0xcbf43926);
+        assert_eq!(crc32(&[b"1234", b"56789"]), 0xcbf43926);
+        assert_eq!(adler32(b"Wikipedia"), 0x11e60398);
+        assert_eq!(adler32(&[]), 1);
+        // Long enough for the modulo to be applied in between
+        assert_eq!(adler32(&[0xff; 100_000]), 0x149a302c);
+    }
+
+    #[test]
+    fn stored_blocks_hold_the_data() {
+        for len in [
+            0,
+            1,
+            MAX_STORED_BLOCK,
+            MAX_STORED_BLOCK + 1,
+            3 * MAX_STORED_BLOCK + 7,
+        ] {
+            let data: Vec<u8> = (0..len).map(|i| (i * 7 % 251) as u8).collect();
+            assert_eq!(inflate_stored(&zlib_stored(&data)), data);
+        }
+    }
+
+    #[test]
+    fn encodes_header_and_scanlines() {
+        let pixels: Vec<u8> = (0..3 * 2 * 4).map(|i| i as u8).collect();
+        let png = encode_rgba8(3, 2, &pixels);
+        let chunks = chunks(&png);
+        let kinds: Vec<&[u8; 4]> = chunks.iter().map(|(kind, _)| kind).collect();
+        assert_eq!(kinds, [b"IHDR", b"IDAT", b"IEND"]);
+
+        let header = chunks[0].1;
+        assert_eq!(header[..8], [0, 0, 0, 3, 0, 0, 0, 2]);
+        assert_eq!(header[8..], [8, 6, 0, 0, 0]);
+
+        let scanlines = inflate_stored(chunks[1].1);
+        assert_eq!(scanlines.len(), 2 * (1 + 3 * 4));
+        assert_eq!(scanlines[0], 0);
+        assert_eq!(scanlines[1..13], pixels[..12]);
+        assert_eq!(scanlines[13], 0);
+        assert_eq!(scanlines[14..], pixels[12..]);
+        assert!(chunks[2].1.is_empty());
+    }
+
+    #[test]
+    fn encodes_empty_images() {
+        let png = encode_rgba8(0, 0, &[]);
+        assert_eq!(inflate_stored(chunks(&png)[1].1), []);
+    }
+}
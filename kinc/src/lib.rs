@@ -1,52 +1,178 @@
-#![no_std]
+#![cfg_attr(not(test), no_std)]
 #![allow(clippy::from_over_into)]
 #![warn(clippy::missing_safety_doc)]
 #![deny(unsafe_op_in_unsafe_fn)]
 
+extern crate alloc;
+
+mod color;
 pub mod g4;
+pub mod game_loop;
+pub mod image;
+#[cfg(feature = "log")]
+pub mod logger;
+pub mod math;
+pub mod network;
+pub mod random;
+pub mod reflection;
+pub mod simd;
 mod sys;
+pub mod system;
+pub mod threads;
+pub mod video;
+pub mod vr;
 
-use core::{cell::UnsafeCell, ffi::CStr, mem::MaybeUninit, ptr::NonNull};
+use alloc::vec;
+use core::{cell::UnsafeCell, ffi::CStr, mem::MaybeUninit, ptr::NonNull, time::Duration};
 // use std::process::Termination;
 use g4::Graphics4;
+use image::Image;
 
-extern crate krafix;
+pub use color::*;
 
-pub use krafix::compile_shader as krafix_compile;
+pub use krafix_macros::compile_shader as krafix_compile;
+pub use krafix_macros::compile_shader_file as krafix_compile_file;
+pub use krafix_macros::shader_interface as krafix_shader_interface;
+pub use krafix_macros::shader_interface_file as krafix_shader_interface_file;
 
 #[cfg(all(feature = "opengl", any(target_os = "linux", target_os = "android")))]
 #[macro_export]
 macro_rules! compile_shader {
-    ($t:ident, $source:expr) => {
-        $crate::krafix_compile!($t, essl, $source)
+    ($t:ident, $source:expr $(, $($args:tt)*)?) => {
+        $crate::krafix_compile!($t, essl, $source, krate = $crate $(, $($args)*)?)
+    };
+}
+#[cfg(all(feature = "opengl", any(target_os = "linux", target_os = "android")))]
+#[macro_export]
+macro_rules! compile_shader_file {
+    ($t:ident, $path:expr $(, $($args:tt)*)?) => {
+        $crate::krafix_compile_file!($t, essl, $path, krate = $crate $(, $($args)*)?)
+    };
+}
+#[cfg(all(feature = "opengl", any(target_os = "linux", target_os = "android")))]
+#[macro_export]
+macro_rules! shader_interface {
+    ($t:ident, $source:expr $(, $($args:tt)*)?) => {
+        $crate::krafix_shader_interface!($t, essl, $source, krate = $crate $(, $($args)*)?);
+    };
+}
+#[cfg(all(feature = "opengl", any(target_os = "linux", target_os = "android")))]
+#[macro_export]
+macro_rules! shader_interface_file {
+    ($t:ident, $path:expr $(, $($args:tt)*)?) => {
+        $crate::krafix_shader_interface_file!($t, essl, $path, krate = $crate $(, $($args)*)?);
     };
 }
 #[cfg(all(feature = "opengl", target_os = "windows"))]
 #[macro_export]
 macro_rules! compile_shader {
-    ($t:ident, $source:expr) => {
-        $crate::krafix_compile!($t, glsl, $source)
+    ($t:ident, $source:expr $(, $($args:tt)*)?) => {
+        $crate::krafix_compile!($t, glsl, $source, krate = $crate $(, $($args)*)?)
+    };
+}
+#[cfg(all(feature = "opengl", target_os = "windows"))]
+#[macro_export]
+macro_rules! compile_shader_file {
+    ($t:ident, $path:expr $(, $($args:tt)*)?) => {
+        $crate::krafix_compile_file!($t, glsl, $path, krate = $crate $(, $($args)*)?)
+    };
+}
+#[cfg(all(feature = "opengl", target_os = "windows"))]
+#[macro_export]
+macro_rules! shader_interface {
+    ($t:ident, $source:expr $(, $($args:tt)*)?) => {
+        $crate::krafix_shader_interface!($t, glsl, $source, krate = $crate $(, $($args)*)?);
+    };
+}
+#[cfg(all(feature = "opengl", target_os = "windows"))]
+#[macro_export]
+macro_rules! shader_interface_file {
+    ($t:ident, $path:expr $(, $($args:tt)*)?) => {
+        $crate::krafix_shader_interface_file!($t, glsl, $path, krate = $crate $(, $($args)*)?);
     };
 }
 #[cfg(all(feature = "metal", not(feature = "opengl")))]
 #[macro_export]
 macro_rules! compile_shader {
-    ($t:ident, $source:expr) => {
-        $crate::krafix_compile!($t, metal, $source)
+    ($t:ident, $source:expr $(, $($args:tt)*)?) => {
+        $crate::krafix_compile!($t, metal, $source, krate = $crate $(, $($args)*)?)
+    };
+}
+#[cfg(all(feature = "metal", not(feature = "opengl")))]
+#[macro_export]
+macro_rules! compile_shader_file {
+    ($t:ident, $path:expr $(, $($args:tt)*)?) => {
+        $crate::krafix_compile_file!($t, metal, $path, krate = $crate $(, $($args)*)?)
+    };
+}
+#[cfg(all(feature = "metal", not(feature = "opengl")))]
+#[macro_export]
+macro_rules! shader_interface {
+    ($t:ident, $source:expr $(, $($args:tt)*)?) => {
+        $crate::krafix_shader_interface!($t, metal, $source, krate = $crate $(, $($args)*)?);
+    };
+}
+#[cfg(all(feature = "metal", not(feature = "opengl")))]
+#[macro_export]
+macro_rules! shader_interface_file {
+    ($t:ident, $path:expr $(, $($args:tt)*)?) => {
+        $crate::krafix_shader_interface_file!($t, metal, $path, krate = $crate $(, $($args)*)?);
     };
 }
 #[cfg(all(feature = "vulkan", not(feature = "opengl")))]
 #[macro_export]
 macro_rules! compile_shader {
-    ($t:ident, $source:expr) => {
-        $crate::krafix_compile!($t, spirv, $source)
+    ($t:ident, $source:expr $(, $($args:tt)*)?) => {
+        $crate::krafix_compile!($t, spirv, $source, krate = $crate $(, $($args)*)?)
+    };
+}
+#[cfg(all(feature = "vulkan", not(feature = "opengl")))]
+#[macro_export]
+macro_rules! compile_shader_file {
+    ($t:ident, $path:expr $(, $($args:tt)*)?) => {
+        $crate::krafix_compile_file!($t, spirv, $path, krate = $crate $(, $($args)*)?)
+    };
+}
+#[cfg(all(feature = "vulkan", not(feature = "opengl")))]
+#[macro_export]
+macro_rules! shader_interface {
+    ($t:ident, $source:expr $(, $($args:tt)*)?) => {
+        $crate::krafix_shader_interface!($t, spirv, $source, krate = $crate $(, $($args)*)?);
+    };
+}
+#[cfg(all(feature = "vulkan", not(feature = "opengl")))]
+#[macro_export]
+macro_rules! shader_interface_file {
+    ($t:ident, $path:expr $(, $($args:tt)*)?) => {
+        $crate::krafix_shader_interface_file!($t, spirv, $path, krate = $crate $(, $($args)*)?);
     };
 }
 #[cfg(any(feature = "d3d12", feature = "d3d11"))]
 #[macro_export]
 macro_rules! compile_shader {
-    ($t:ident, $source:expr) => {
-        $crate::krafix_compile!($t, d3d11, $source)
+    ($t:ident, $source:expr $(, $($args:tt)*)?) => {
+        $crate::krafix_compile!($t, d3d11, $source, krate = $crate $(, $($args)*)?)
+    };
+}
+#[cfg(any(feature = "d3d12", feature = "d3d11"))]
+#[macro_export]
+macro_rules! compile_shader_file {
+    ($t:ident, $path:expr $(, $($args:tt)*)?) => {
+        $crate::krafix_compile_file!($t, d3d11, $path, krate = $crate $(, $($args)*)?)
+    };
+}
+#[cfg(any(feature = "d3d12", feature = "d3d11"))]
+#[macro_export]
+macro_rules! shader_interface {
+    ($t:ident, $source:expr $(, $($args:tt)*)?) => {
+        $crate::krafix_shader_interface!($t, d3d11, $source, krate = $crate $(, $($args)*)?);
+    };
+}
+#[cfg(any(feature = "d3d12", feature = "d3d11"))]
+#[macro_export]
+macro_rules! shader_interface_file {
+    ($t:ident, $path:expr $(, $($args:tt)*)?) => {
+        $crate::krafix_shader_interface_file!($t, d3d11, $path, krate = $crate $(, $($args)*)?);
     };
 }
 
@@ -337,10 +463,73 @@ pub struct Window {
     window: i32,
 }
 
-impl Window {}
+impl Window {
+    pub fn width(&self) -> i32 {
+        // Safety: self.window is a valid window index
+        unsafe { kinc_window_width(self.window) }
+    }
+
+    pub fn height(&self) -> i32 {
+        // Safety: self.window is a valid window index
+        unsafe { kinc_window_height(self.window) }
+    }
+
+    /// Reads back what has been drawn into the window's framebuffer this frame,
+    /// so call it after drawing and before [`Graphics4::swap_buffers`].
+    ///
+    /// Framebuffer readback is only implemented for OpenGL so far,
+    /// the other graphics backends return a [`ScreenshotError`], as do minimized windows.
+    pub fn screenshot(&self) -> Result<Image, ScreenshotError> {
+        let (width, height) = (self.width(), self.height());
+        if width <= 0 || height <= 0 {
+            return Err(ScreenshotError);
+        }
+        let stride = width as usize * 4;
+        let mut pixels = vec![0_u8; stride * height as usize];
+        // Safety: self.window is a valid window index, and pixels holds width * height RGBA8 pixels
+        if !unsafe { kinc_rust_framebuffer_get_pixels(self.window, pixels.as_mut_ptr()) } {
+            return Err(ScreenshotError);
+        }
+        // The bottom row comes first
+        let flipped = pixels
+            .chunks_exact(stride)
+            .rev()
+            .flatten()
+            .copied()
+            .collect();
+        Ok(Image::from_bytes(
+            flipped,
+            width,
+            height,
+            image::ImageFormat::RGBA32,
+        ))
+    }
+}
+
+#[derive(Debug)]
+pub struct ScreenshotError;
+
+impl core::fmt::Display for ScreenshotError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "the graphics backend can't read back the framebuffer")
+    }
+}
+
+impl core::error::Error for ScreenshotError {}
 
 pub trait Callbacks {
     fn update(&mut self, _kinc: &mut Kinc) {}
+
+    /// Called zero or more times per frame with the same `dt`, when wrapped in a [`game_loop::GameLoop`].
+    fn fixed_update(&mut self, _kinc: &mut Kinc, _dt: Duration) {}
+
+    /// Called once per frame after the fixed updates, when wrapped in a [`game_loop::GameLoop`].
+    /// `alpha` is how far the time is between the last fixed update and the next one.
+    ///
+    /// Calls [`Callbacks::update`] by default, so wrapped callbacks keep their per-frame work.
+    fn render(&mut self, kinc: &mut Kinc, _alpha: f32) {
+        self.update(kinc);
+    }
 }
 
 #[cfg(any(target_os = "android", target_os = "ios"))]
@@ -0,0 +1,136 @@
+//! A [`log`] backend that writes through `kinc_log`, which ends up in the console, or in Logcat on Android.
+//!
+//! What Kinc logs itself is routed the other way, into records with the target `kinc` and the C file and line of the call.
+//! `kinc_error` and failed `kinc_affirm`s are logged as errors and then panic, the panic can't unwind through Kinc and aborts.
+
+use alloc::{ffi::CString, format};
+use core::ffi::{c_char, c_int, CStr};
+
+use log::{Level, LevelFilter, Log, Metadata, Record, SetLoggerError};
+
+use crate::sys::*;
+
+struct KincLogger;
+
+static LOGGER: KincLogger = KincLogger;
+
+/// Installs the logger, records above `level` are discarded. Also calls [`route_kinc_log`].
+pub fn init(level: LevelFilter) -> Result<(), SetLoggerError> {
+    log::set_logger(&LOGGER)?;
+    log::set_max_level(level);
+    route_kinc_log();
+    Ok(())
+}
+
+/// Sends what Kinc logs, and its errors, to the installed logger, whichever backend that is.
+/// Call it before starting threads that use Kinc.
+pub fn route_kinc_log() {
+    // Safety: the hook stays valid for the rest of the program
+    unsafe { kinc_rust_set_log_hook(Some(hook)) }
+}
+
+fn kinc_level(level: Level) -> kinc_log_level_t {
+    match level {
+        Level::Error => kinc_log_level_t_KINC_LOG_LEVEL_ERROR,
+        Level::Warn => kinc_log_level_t_KINC_LOG_LEVEL_WARNING,
+        Level::Info | Level::Debug | Level::Trace => kinc_log_level_t_KINC_LOG_LEVEL_INFO,
+    }
+}
+
+fn level(level: kinc_log_level_t) -> Level {
+    if level == kinc_log_level_t_KINC_LOG_LEVEL_ERROR {
+        Level::Error
+    } else if level == kinc_log_level_t_KINC_LOG_LEVEL_WARNING {
+        Level::Warn
+    } else {
+        Level::Info
+    }
+}
+
+extern "C" fn hook(
+    level: kinc_log_level_t,
+    file: *const c_char,
+    line: c_int,
+    message: *const c_char,
+    error: bool,
+) {
+    // Safety: the shim passes the __FILE__ of the call and the formatted message
+    let (file, message) = unsafe { (CStr::from_ptr(file), CStr::from_ptr(message)) };
+    report(
+        self::level(level),
+        &file.to_string_lossy(),
+        line as u32,
+        &message.to_string_lossy(),
+        error,
+    );
+}
+
+fn report(level: Level, file: &str, line: u32, message: &str, error: bool) {
+    if level <= log::max_level() {
+        log::logger().log(
+            &Record::builder()
+                .args(format_args!("{}", message))
+                .level(level)
+                .target("kinc")
+                .file(Some(file))
+                .line(Some(line))
+                .build(),
+        );
+    }
+    if error {
+        panic!("{}:{}: {}", file, line, message);
+    }
+}
+
+impl Log for KincLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        // Kinc only knows three levels, so the finer ones are kept in the message
+        let message = match record.level() {
+            Level::Debug | Level::Trace => {
+                format!(
+                    "[{}] {}: {}",
+                    record.level(),
+                    record.target(),
+                    record.args()
+                )
+            }
+            _ => format!("{}: {}", record.target(), record.args()),
+        };
+        let message = CString::new(message.replace('\0', "\\0")).unwrap();
+        // Safety: the format string only consumes the single C string argument
+        unsafe { kinc_log(kinc_level(record.level()), c"%s".as_ptr(), message.as_ptr()) }
+    }
+
+    fn flush(&self) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn levels_round_trip() {
+        for l in [Level::Error, Level::Warn, Level::Info] {
+            assert_eq!(level(kinc_level(l)), l);
+        }
+        assert_eq!(level(kinc_level(Level::Trace)), Level::Info);
+    }
+
+    #[test]
+    fn messages_are_not_fatal() {
+        report(Level::Warn, "texture.c", 12, "Slow path", false);
+    }
+
+    #[test]
+    #[should_panic(expected = "texture.c:12: Out of memory")]
+    fn errors_panic_with_their_location() {
+        report(Level::Error, "texture.c", 12, "Out of memory", true);
+    }
+}
@@ -0,0 +1,1134 @@
+//! Vectors, matrices and quaternions with the same memory layout as Kinc's math types.
+//!
+//! Matrices are column-major, like `kinc_matrix3x3_t` and `kinc_matrix4x4_t`,
+//! and can be passed to the uniform setters of [`RenderPass`](crate::g4::RenderPass) directly.
+
+use core::ops::{
+    Add, AddAssign, Div, DivAssign, Index, IndexMut, Mul, MulAssign, Neg, Sub, SubAssign,
+};
+
+use crate::sys::*;
+
+fn sqrt(value: f32) -> f32 {
+    // Safety: plain math function without side effects
+    unsafe { kinc_sqrt(value) }
+}
+
+fn sin(value: f32) -> f32 {
+    // Safety: plain math function without side effects
+    unsafe { kinc_sin(value) }
+}
+
+fn cos(value: f32) -> f32 {
+    // Safety: plain math function without side effects
+    unsafe { kinc_cos(value) }
+}
+
+fn tan(value: f32) -> f32 {
+    // Safety: plain math function without side effects
+    unsafe { kinc_tan(value) }
+}
+
+fn acos(value: f32) -> f32 {
+    // Safety: plain math function without side effects
+    unsafe { kinc_acos(value) }
+}
+
+macro_rules! vector {
+    ($name:ident, $raw:ident, $n:literal, $($field:ident),+) => {
+        #[derive(Copy, Clone, Debug, Default, PartialEq)]
+        #[repr(C)]
+        pub struct $name {
+            $(pub $field: f32,)+
+        }
+
+        impl $name {
+            pub const ZERO: Self = Self { $($field: 0.0,)+ };
+            pub const ONE: Self = Self { $($field: 1.0,)+ };
+
+            pub const fn new($($field: f32),+) -> Self {
+                Self { $($field,)+ }
+            }
+
+            pub const fn splat(value: f32) -> Self {
+                Self { $($field: value,)+ }
+            }
+
+            pub fn dot(self, other: Self) -> f32 {
+                0.0 $(+ self.$field * other.$field)+
+            }
+
+            pub fn length_squared(self) -> f32 {
+                self.dot(self)
+            }
+
+            pub fn length(self) -> f32 {
+                sqrt(self.length_squared())
+            }
+
+            pub fn distance(self, other: Self) -> f32 {
+                (self - other).length()
+            }
+
+            /// Returns a vector of length 1, or zero if the vector has no length.
+            pub fn normalize(self) -> Self {
+                let length = self.length();
+                if length == 0.0 {
+                    Self::ZERO
+                } else {
+                    self / length
+                }
+            }
+
+            pub fn lerp(self, other: Self, t: f32) -> Self {
+                self + (other - self) * t
+            }
+
+            pub fn min(self, other: Self) -> Self {
+                Self { $($field: self.$field.min(other.$field),)+ }
+            }
+
+            pub fn max(self, other: Self) -> Self {
+                Self { $($field: self.$field.max(other.$field),)+ }
+            }
+
+            pub fn to_array(self) -> [f32; $n] {
+                [$(self.$field),+]
+            }
+        }
+
+        impl Add for $name {
+            type Output = Self;
+            fn add(self, rhs: Self) -> Self {
+                Self { $($field: self.$field + rhs.$field,)+ }
+            }
+        }
+
+        impl Sub for $name {
+            type Output = Self;
+            fn sub(self, rhs: Self) -> Self {
+                Self { $($field: self.$field - rhs.$field,)+ }
+            }
+        }
+
+        impl Mul for $name {
+            type Output = Self;
+            fn mul(self, rhs: Self) -> Self {
+                Self { $($field: self.$field * rhs.$field,)+ }
+            }
+        }
+
+        impl Mul<f32> for $name {
+            type Output = Self;
+            fn mul(self, rhs: f32) -> Self {
+                Self { $($field: self.$field * rhs,)+ }
+            }
+        }
+
+        impl Mul<$name> for f32 {
+            type Output = $name;
+            fn mul(self, rhs: $name) -> $name {
+                rhs * self
+            }
+        }
+
+        impl Div<f32> for $name {
+            type Output = Self;
+            fn div(self, rhs: f32) -> Self {
+                Self { $($field: self.$field / rhs,)+ }
+            }
+        }
+
+        impl Neg for $name {
+            type Output = Self;
+            fn neg(self) -> Self {
+                Self { $($field: -self.$field,)+ }
+            }
+        }
+
+        impl AddAssign for $name {
+            fn add_assign(&mut self, rhs: Self) {
+                *self = *self + rhs;
+            }
+        }
+
+        impl SubAssign for $name {
+            fn sub_assign(&mut self, rhs: Self) {
+                *self = *self - rhs;
+            }
+        }
+
+        impl MulAssign<f32> for $name {
+            fn mul_assign(&mut self, rhs: f32) {
+                *self = *self * rhs;
+            }
+        }
+
+        impl DivAssign<f32> for $name {
+            fn div_assign(&mut self, rhs: f32) {
+                *self = *self / rhs;
+            }
+        }
+
+        impl From<[f32; $n]> for $name {
+            fn from([$($field),+]: [f32; $n]) -> Self {
+                Self { $($field,)+ }
+            }
+        }
+
+        impl From<$name> for [f32; $n] {
+            fn from(v: $name) -> Self {
+                v.to_array()
+            }
+        }
+
+        impl From<$raw> for $name {
+            fn from(v: $raw) -> Self {
+                Self { $($field: v.$field,)+ }
+            }
+        }
+
+        impl Into<$raw> for $name {
+            fn into(self) -> $raw {
+                $raw { $($field: self.$field,)+ }
+            }
+        }
+
+        impl AsRef<$raw> for $name {
+            fn as_ref(&self) -> &$raw {
+                // Safety: both types are #[repr(C)] structs with the same fields
+                unsafe { &*(self as *const Self).cast() }
+            }
+        }
+    };
+}
+
+vector!(Vec2, kinc_vector2_t, 2, x, y);
+vector!(Vec3, kinc_vector3_t, 3, x, y, z);
+vector!(Vec4, kinc_vector4_t, 4, x, y, z, w);
+
+impl Vec3 {
+    pub const X: Self = Self::new(1.0, 0.0, 0.0);
+    pub const Y: Self = Self::new(0.0, 1.0, 0.0);
+    pub const Z: Self = Self::new(0.0, 0.0, 1.0);
+
+    pub fn cross(self, other: Self) -> Self {
+        Self::new(
+            self.y * other.z - self.z * other.y,
+            self.z * other.x - self.x * other.z,
+            self.x * other.y - self.y * other.x,
+        )
+    }
+
+    pub fn extend(self, w: f32) -> Vec4 {
+        Vec4::new(self.x, self.y, self.z, w)
+    }
+}
+
+impl Vec4 {
+    pub fn truncate(self) -> Vec3 {
+        Vec3::new(self.x, self.y, self.z)
+    }
+}
+
+/// The depth range and y direction of normalized device coordinates.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ClipSpace {
+    /// OpenGL's convention, depth goes from -1 to 1 and y points up.
+    NegativeOneToOne,
+    /// Direct3D's and Metal's convention, depth goes from 0 to 1 and y points up.
+    ZeroToOne,
+    /// Vulkan's convention, depth goes from 0 to 1 and y points down.
+    ZeroToOneYDown,
+}
+
+impl ClipSpace {
+    /// The clip space of the graphics backend Kinc was built with.
+    pub const fn current() -> Self {
+        if cfg!(feature = "opengl") {
+            ClipSpace::NegativeOneToOne
+        } else if cfg!(feature = "vulkan") {
+            ClipSpace::ZeroToOneYDown
+        } else {
+            ClipSpace::ZeroToOne
+        }
+    }
+
+    /// Negates y after a projection for the conventions where it points down.
+    fn orient(self, mut m: Mat4) -> Mat4 {
+        if self == ClipSpace::ZeroToOneYDown {
+            for col in 0..4 {
+                m[(col, 1)] = -m[(col, 1)];
+            }
+        }
+        m
+    }
+}
+
+/// A column-major 3x3 matrix.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[repr(C)]
+pub struct Mat3 {
+    pub m: [f32; 9],
+}
+
+impl Mat3 {
+    pub const IDENTITY: Self = Self {
+        m: [1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0],
+    };
+
+    pub const fn from_cols(x: Vec3, y: Vec3, z: Vec3) -> Self {
+        Self {
+            m: [x.x, x.y, x.z, y.x, y.y, y.z, z.x, z.y, z.z],
+        }
+    }
+
+    pub fn col(&self, col: usize) -> Vec3 {
+        Vec3::new(self.m[col * 3], self.m[col * 3 + 1], self.m[col * 3 + 2])
+    }
+
+    pub fn row(&self, row: usize) -> Vec3 {
+        Vec3::new(self.m[row], self.m[3 + row], self.m[6 + row])
+    }
+
+    pub fn transpose(&self) -> Self {
+        Self::from_cols(self.row(0), self.row(1), self.row(2))
+    }
+
+    pub fn determinant(&self) -> f32 {
+        self.col(0).dot(self.col(1).cross(self.col(2)))
+    }
+
+    pub fn from_quat(q: Quat) -> Self {
+        let m = Mat4::from_quat(q);
+        Self::from_cols(
+            m.col(0).truncate(),
+            m.col(1).truncate(),
+            m.col(2).truncate(),
+        )
+    }
+}
+
+impl Default for Mat3 {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+/// Indexed by `(column, row)`.
+impl Index<(usize, usize)> for Mat3 {
+    type Output = f32;
+    fn index(&self, (col, row): (usize, usize)) -> &f32 {
+        &self.m[col * 3 + row]
+    }
+}
+
+impl IndexMut<(usize, usize)> for Mat3 {
+    fn index_mut(&mut self, (col, row): (usize, usize)) -> &mut f32 {
+        &mut self.m[col * 3 + row]
+    }
+}
+
+impl Mul for Mat3 {
+    type Output = Self;
+    fn mul(self, rhs: Self) -> Self {
+        Self::from_cols(self * rhs.col(0), self * rhs.col(1), self * rhs.col(2))
+    }
+}
+
+impl Mul<Vec3> for Mat3 {
+    type Output = Vec3;
+    fn mul(self, rhs: Vec3) -> Vec3 {
+        self.col(0) * rhs.x + self.col(1) * rhs.y + self.col(2) * rhs.z
+    }
+}
+
+impl From<kinc_matrix3x3_t> for Mat3 {
+    fn from(m: kinc_matrix3x3_t) -> Self {
+        Self { m: m.m }
+    }
+}
+
+impl Into<kinc_matrix3x3_t> for Mat3 {
+    fn into(self) -> kinc_matrix3x3_t {
+        kinc_matrix3x3_t { m: self.m }
+    }
+}
+
+impl AsRef<kinc_matrix3x3_t> for Mat3 {
+    fn as_ref(&self) -> &kinc_matrix3x3_t {
+        // Safety: both types are #[repr(C)] structs containing a single [f32; 9]
+        unsafe { &*(self as *const Self).cast() }
+    }
+}
+
+/// A column-major 4x4 matrix.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[repr(C)]
+pub struct Mat4 {
+    pub m: [f32; 16],
+}
+
+impl Mat4 {
+    pub const IDENTITY: Self = Self::from_cols(
+        Vec4::new(1.0, 0.0, 0.0, 0.0),
+        Vec4::new(0.0, 1.0, 0.0, 0.0),
+        Vec4::new(0.0, 0.0, 1.0, 0.0),
+        Vec4::new(0.0, 0.0, 0.0, 1.0),
+    );
+
+    pub const fn from_cols(x: Vec4, y: Vec4, z: Vec4, w: Vec4) -> Self {
+        Self {
+            m: [
+                x.x, x.y, x.z, x.w, y.x, y.y, y.z, y.w, z.x, z.y, z.z, z.w, w.x, w.y, w.z, w.w,
+            ],
+        }
+    }
+
+    pub fn col(&self, col: usize) -> Vec4 {
+        Vec4::new(
+            self.m[col * 4],
+            self.m[col * 4 + 1],
+            self.m[col * 4 + 2],
+            self.m[col * 4 + 3],
+        )
+    }
+
+    pub fn row(&self, row: usize) -> Vec4 {
+        Vec4::new(
+            self.m[row],
+            self.m[4 + row],
+            self.m[8 + row],
+            self.m[12 + row],
+        )
+    }
+
+    pub fn transpose(&self) -> Self {
+        Self::from_cols(self.row(0), self.row(1), self.row(2), self.row(3))
+    }
+
+    pub fn translation(v: Vec3) -> Self {
+        let mut m = Self::IDENTITY;
+        m.m[12] = v.x;
+        m.m[13] = v.y;
+        m.m[14] = v.z;
+        m
+    }
+
+    pub fn scale(v: Vec3) -> Self {
+        let mut m = Self::IDENTITY;
+        m.m[0] = v.x;
+        m.m[5] = v.y;
+        m.m[10] = v.z;
+        m
+    }
+
+    pub fn rotation_x(angle: f32) -> Self {
+        let (s, c) = (sin(angle), cos(angle));
+        let mut m = Self::IDENTITY;
+        m.m[5] = c;
+        m.m[6] = s;
+        m.m[9] = -s;
+        m.m[10] = c;
+        m
+    }
+
+    pub fn rotation_y(angle: f32) -> Self {
+        let (s, c) = (sin(angle), cos(angle));
+        let mut m = Self::IDENTITY;
+        m.m[0] = c;
+        m.m[2] = -s;
+        m.m[8] = s;
+        m.m[10] = c;
+        m
+    }
+
+    pub fn rotation_z(angle: f32) -> Self {
+        let (s, c) = (sin(angle), cos(angle));
+        let mut m = Self::IDENTITY;
+        m.m[0] = c;
+        m.m[1] = s;
+        m.m[4] = -s;
+        m.m[5] = c;
+        m
+    }
+
+    pub fn from_quat(q: Quat) -> Self {
+        let (x2, y2, z2) = (q.x + q.x, q.y + q.y, q.z + q.z);
+        let (xx, xy, xz) = (q.x * x2, q.x * y2, q.x * z2);
+        let (yy, yz, zz) = (q.y * y2, q.y * z2, q.z * z2);
+        let (wx, wy, wz) = (q.w * x2, q.w * y2, q.w * z2);
+        Self::from_cols(
+            Vec4::new(1.0 - (yy + zz), xy + wz, xz - wy, 0.0),
+            Vec4::new(xy - wz, 1.0 - (xx + zz), yz + wx, 0.0),
+            Vec4::new(xz + wy, yz - wx, 1.0 - (xx + yy), 0.0),
+            Vec4::new(0.0, 0.0, 0.0, 1.0),
+        )
+    }
+
+    /// A right-handed view matrix looking from `eye` at `center`.
+    pub fn look_at(eye: Vec3, center: Vec3, up: Vec3) -> Self {
+        let f = (center - eye).normalize();
+        let s = f.cross(up).normalize();
+        let u = s.cross(f);
+        Self::from_cols(
+            Vec4::new(s.x, u.x, -f.x, 0.0),
+            Vec4::new(s.y, u.y, -f.y, 0.0),
+            Vec4::new(s.z, u.z, -f.z, 0.0),
+            Vec4::new(-s.dot(eye), -u.dot(eye), f.dot(eye), 1.0),
+        )
+    }
+
+    /// A right-handed perspective projection for the active graphics backend, `fov_y` is in radians.
+    pub fn perspective(fov_y: f32, aspect: f32, near: f32, far: f32) -> Self {
+        Self::perspective_for(ClipSpace::current(), fov_y, aspect, near, far)
+    }
+
+    pub fn perspective_for(clip: ClipSpace, fov_y: f32, aspect: f32, near: f32, far: f32) -> Self {
+        let f = 1.0 / tan(fov_y / 2.0);
+        let mut m = Self { m: [0.0; 16] };
+        m.m[0] = f / aspect;
+        m.m[5] = f;
+        m.m[11] = -1.0;
+        match clip {
+            ClipSpace::NegativeOneToOne => {
+                m.m[10] = (far + near) / (near - far);
+                m.m[14] = 2.0 * far * near / (near - far);
+            }
+            ClipSpace::ZeroToOne | ClipSpace::ZeroToOneYDown => {
+                m.m[10] = far / (near - far);
+                m.m[14] = far * near / (near - far);
+            }
+        }
+        clip.orient(m)
+    }
+
+    /// A right-handed off-center perspective projection for the active graphics backend,
+    /// the bounds are given on the near plane.
+    pub fn frustum(left: f32, right: f32, bottom: f32, top: f32, near: f32, far: f32) -> Self {
+        Self::frustum_for(ClipSpace::current(), left, right, bottom, top, near, far)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn frustum_for(
+        clip: ClipSpace,
+        left: f32,
+        right: f32,
+        bottom: f32,
+        top: f32,
+        near: f32,
+        far: f32,
+    ) -> Self {
+        let mut m = Self { m: [0.0; 16] };
+        m.m[0] = 2.0 * near / (right - left);
+        m.m[5] = 2.0 * near / (top - bottom);
+        m.m[8] = (right + left) / (right - left);
+        m.m[9] = (top + bottom) / (top - bottom);
+        m.m[11] = -1.0;
+        match clip {
+            ClipSpace::NegativeOneToOne => {
+                m.m[10] = (far + near) / (near - far);
+                m.m[14] = 2.0 * far * near / (near - far);
+            }
+            ClipSpace::ZeroToOne | ClipSpace::ZeroToOneYDown => {
+                m.m[10] = far / (near - far);
+                m.m[14] = far * near / (near - far);
+            }
+        }
+        clip.orient(m)
+    }
+
+    /// A right-handed orthographic projection for the active graphics backend.
+    pub fn orthographic(left: f32, right: f32, bottom: f32, top: f32, near: f32, far: f32) -> Self {
+        Self::orthographic_for(ClipSpace::current(), left, right, bottom, top, near, far)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn orthographic_for(
+        clip: ClipSpace,
+        left: f32,
+        right: f32,
+        bottom: f32,
+        top: f32,
+        near: f32,
+        far: f32,
+    ) -> Self {
+        let mut m = Self::IDENTITY;
+        m.m[0] = 2.0 / (right - left);
+        m.m[5] = 2.0 / (top - bottom);
+        m.m[12] = -(right + left) / (right - left);
+        m.m[13] = -(top + bottom) / (top - bottom);
+        match clip {
+            ClipSpace::NegativeOneToOne => {
+                m.m[10] = -2.0 / (far - near);
+                m.m[14] = -(far + near) / (far - near);
+            }
+            ClipSpace::ZeroToOne | ClipSpace::ZeroToOneYDown => {
+                m.m[10] = -1.0 / (far - near);
+                m.m[14] = -near / (far - near);
+            }
+        }
+        clip.orient(m)
+    }
+
+    pub fn determinant(&self) -> f32 {
+        let [a, b, c, d] = [self.col(0), self.col(1), self.col(2), self.col(3)];
+        let s0 = a.x * b.y - b.x * a.y;
+        let s1 = a.x * b.z - b.x * a.z;
+        let s2 = a.x * b.w - b.x * a.w;
+        let s3 = a.y * b.z - b.y * a.z;
+        let s4 = a.y * b.w - b.y * a.w;
+        let s5 = a.z * b.w - b.z * a.w;
+        let c5 = c.z * d.w - d.z * c.w;
+        let c4 = c.y * d.w - d.y * c.w;
+        let c3 = c.y * d.z - d.y * c.z;
+        let c2 = c.x * d.w - d.x * c.w;
+        let c1 = c.x * d.z - d.x * c.z;
+        let c0 = c.x * d.y - d.x * c.y;
+        s0 * c5 - s1 * c4 + s2 * c3 + s3 * c2 - s4 * c1 + s5 * c0
+    }
+
+    /// Returns `None` if the matrix is singular.
+    pub fn inverse(&self) -> Option<Self> {
+        let [a, b, c, d] = [self.col(0), self.col(1), self.col(2), self.col(3)];
+        let s0 = a.x * b.y - b.x * a.y;
+        let s1 = a.x * b.z - b.x * a.z;
+        let s2 = a.x * b.w - b.x * a.w;
+        let s3 = a.y * b.z - b.y * a.z;
+        let s4 = a.y * b.w - b.y * a.w;
+        let s5 = a.z * b.w - b.z * a.w;
+        let c5 = c.z * d.w - d.z * c.w;
+        let c4 = c.y * d.w - d.y * c.w;
+        let c3 = c.y * d.z - d.y * c.z;
+        let c2 = c.x * d.w - d.x * c.w;
+        let c1 = c.x * d.z - d.x * c.z;
+        let c0 = c.x * d.y - d.x * c.y;
+        let det = s0 * c5 - s1 * c4 + s2 * c3 + s3 * c2 - s4 * c1 + s5 * c0;
+        if det == 0.0 {
+            return None;
+        }
+        let inv = 1.0 / det;
+        Some(Self::from_cols(
+            Vec4::new(
+                b.y * c5 - b.z * c4 + b.w * c3,
+                -a.y * c5 + a.z * c4 - a.w * c3,
+                d.y * s5 - d.z * s4 + d.w * s3,
+                -c.y * s5 + c.z * s4 - c.w * s3,
+            ) * inv,
+            Vec4::new(
+                -b.x * c5 + b.z * c2 - b.w * c1,
+                a.x * c5 - a.z * c2 + a.w * c1,
+                -d.x * s5 + d.z * s2 - d.w * s1,
+                c.x * s5 - c.z * s2 + c.w * s1,
+            ) * inv,
+            Vec4::new(
+                b.x * c4 - b.y * c2 + b.w * c0,
+                -a.x * c4 + a.y * c2 - a.w * c0,
+                d.x * s4 - d.y * s2 + d.w * s0,
+                -c.x * s4 + c.y * s2 - c.w * s0,
+            ) * inv,
+            Vec4::new(
+                -b.x * c3 + b.y * c1 - b.z * c0,
+                a.x * c3 - a.y * c1 + a.z * c0,
+                -d.x * s3 + d.y * s1 - d.z * s0,
+                c.x * s3 - c.y * s1 + c.z * s0,
+            ) * inv,
+        ))
+    }
+
+    pub fn transform_point(&self, p: Vec3) -> Vec3 {
+        let v = *self * p.extend(1.0);
+        v.truncate() / v.w
+    }
+
+    pub fn transform_vector(&self, v: Vec3) -> Vec3 {
+        (*self * v.extend(0.0)).truncate()
+    }
+}
+
+impl Default for Mat4 {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+/// Indexed by `(column, row)`.
+impl Index<(usize, usize)> for Mat4 {
+    type Output = f32;
+    fn index(&self, (col, row): (usize, usize)) -> &f32 {
+        &self.m[col * 4 + row]
+    }
+}
+
+impl IndexMut<(usize, usize)> for Mat4 {
+    fn index_mut(&mut self, (col, row): (usize, usize)) -> &mut f32 {
+        &mut self.m[col * 4 + row]
+    }
+}
+
+impl Mul for Mat4 {
+    type Output = Self;
+    fn mul(self, rhs: Self) -> Self {
+        Self::from_cols(
+            self * rhs.col(0),
+            self * rhs.col(1),
+            self * rhs.col(2),
+            self * rhs.col(3),
+        )
+    }
+}
+
+impl MulAssign for Mat4 {
+    fn mul_assign(&mut self, rhs: Self) {
+        *self = *self * rhs;
+    }
+}
+
+impl Mul<Vec4> for Mat4 {
+    type Output = Vec4;
+    fn mul(self, rhs: Vec4) -> Vec4 {
+        self.col(0) * rhs.x + self.col(1) * rhs.y + self.col(2) * rhs.z + self.col(3) * rhs.w
+    }
+}
+
+impl From<kinc_matrix4x4_t> for Mat4 {
+    fn from(m: kinc_matrix4x4_t) -> Self {
+        Self { m: m.m }
+    }
+}
+
+impl Into<kinc_matrix4x4_t> for Mat4 {
+    fn into(self) -> kinc_matrix4x4_t {
+        kinc_matrix4x4_t { m: self.m }
+    }
+}
+
+impl AsRef<kinc_matrix4x4_t> for Mat4 {
+    fn as_ref(&self) -> &kinc_matrix4x4_t {
+        // Safety: both types are #[repr(C)] structs containing a single [f32; 16]
+        unsafe { &*(self as *const Self).cast() }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[repr(C)]
+pub struct Quat {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    pub w: f32,
+}
+
+impl Quat {
+    pub const IDENTITY: Self = Self::new(0.0, 0.0, 0.0, 1.0);
+
+    pub const fn new(x: f32, y: f32, z: f32, w: f32) -> Self {
+        Self { x, y, z, w }
+    }
+
+    /// The rotation of an orthonormal matrix.
+    pub fn from_mat3(m: Mat3) -> Self {
+        let [x, y, z] = [m.col(0), m.col(1), m.col(2)];
+        // Divides by the largest component to stay accurate
+        let trace = x.x + y.y + z.z;
+        if trace > 0.0 {
+            let s = sqrt(trace + 1.0) * 2.0;
+            Self::new((y.z - z.y) / s, (z.x - x.z) / s, (x.y - y.x) / s, s / 4.0)
+        } else if x.x > y.y && x.x > z.z {
+            let s = sqrt(1.0 + x.x - y.y - z.z) * 2.0;
+            Self::new(s / 4.0, (y.x + x.y) / s, (z.x + x.z) / s, (y.z - z.y) / s)
+        } else if y.y > z.z {
+            let s = sqrt(1.0 + y.y - x.x - z.z) * 2.0;
+            Self::new((y.x + x.y) / s, s / 4.0, (z.y + y.z) / s, (z.x - x.z) / s)
+        } else {
+            let s = sqrt(1.0 + z.z - x.x - y.y) * 2.0;
+            Self::new((z.x + x.z) / s, (z.y + y.z) / s, s / 4.0, (x.y - y.x) / s)
+        }
+    }
+
+    /// `axis` has to be normalized, `angle` is in radians.
+    pub fn from_axis_angle(axis: Vec3, angle: f32) -> Self {
+        let (s, c) = (sin(angle / 2.0), cos(angle / 2.0));
+        Self::new(axis.x * s, axis.y * s, axis.z * s, c)
+    }
+
+    fn as_vec4(self) -> Vec4 {
+        Vec4::new(self.x, self.y, self.z, self.w)
+    }
+
+    fn from_vec4(v: Vec4) -> Self {
+        Self::new(v.x, v.y, v.z, v.w)
+    }
+
+    pub fn dot(self, other: Self) -> f32 {
+        self.as_vec4().dot(other.as_vec4())
+    }
+
+    pub fn length(self) -> f32 {
+        self.as_vec4().length()
+    }
+
+    pub fn normalize(self) -> Self {
+        Self::from_vec4(self.as_vec4().normalize())
+    }
+
+    pub fn conjugate(self) -> Self {
+        Self::new(-self.x, -self.y, -self.z, self.w)
+    }
+
+    /// The inverse of a unit quaternion.
+    pub fn inverse(self) -> Self {
+        Self::from_vec4(self.conjugate().as_vec4() / self.dot(self))
+    }
+
+    pub fn rotate(self, v: Vec3) -> Vec3 {
+        let q = Vec3::new(self.x, self.y, self.z);
+        let t = q.cross(v) * 2.0;
+        v + t * self.w + q.cross(t)
+    }
+
+    /// Spherical linear interpolation along the shortest path.
+    pub fn slerp(self, other: Self, t: f32) -> Self {
+        let mut cos_theta = self.dot(other);
+        let mut other = other.as_vec4();
+        if cos_theta < 0.0 {
+            cos_theta = -cos_theta;
+            other = -other;
+        }
+        if cos_theta > 0.9995 {
+            return Self::from_vec4(self.as_vec4().lerp(other, t)).normalize();
+        }
+        let theta = acos(cos_theta);
+        let sin_theta = sin(theta);
+        let a = sin((1.0 - t) * theta) / sin_theta;
+        let b = sin(t * theta) / sin_theta;
+        Self::from_vec4(self.as_vec4() * a + other * b)
+    }
+}
+
+impl Default for Quat {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl Mul for Quat {
+    type Output = Self;
+    fn mul(self, rhs: Self) -> Self {
+        Self::new(
+            self.w * rhs.x + self.x * rhs.w + self.y * rhs.z - self.z * rhs.y,
+            self.w * rhs.y - self.x * rhs.z + self.y * rhs.w + self.z * rhs.x,
+            self.w * rhs.z + self.x * rhs.y - self.y * rhs.x + self.z * rhs.w,
+            self.w * rhs.w - self.x * rhs.x - self.y * rhs.y - self.z * rhs.z,
+        )
+    }
+}
+
+impl Mul<Vec3> for Quat {
+    type Output = Vec3;
+    fn mul(self, rhs: Vec3) -> Vec3 {
+        self.rotate(rhs)
+    }
+}
+
+impl From<kinc_quaternion_t> for Quat {
+    fn from(q: kinc_quaternion_t) -> Self {
+        Self::new(q.x, q.y, q.z, q.w)
+    }
+}
+
+impl Into<kinc_quaternion_t> for Quat {
+    fn into(self) -> kinc_quaternion_t {
+        kinc_quaternion_t {
+            x: self.x,
+            y: self.y,
+            z: self.z,
+            w: self.w,
+        }
+    }
+}
+
+impl AsRef<kinc_quaternion_t> for Quat {
+    fn as_ref(&self) -> &kinc_quaternion_t {
+        // Safety: both types are #[repr(C)] structs with the same fields
+        unsafe { &*(self as *const Self).cast() }
+    }
+}
+
+#[cfg(feature = "mint")]
+mod mint_impls {
+    use super::*;
+
+    macro_rules! mint_vector {
+        ($name:ident, $mint:ident, $($field:ident),+) => {
+            impl From<mint::$mint<f32>> for $name {
+                fn from(v: mint::$mint<f32>) -> Self {
+                    Self { $($field: v.$field,)+ }
+                }
+            }
+
+            impl From<$name> for mint::$mint<f32> {
+                fn from(v: $name) -> Self {
+                    Self { $($field: v.$field,)+ }
+                }
+            }
+
+            impl mint::IntoMint for $name {
+                type MintType = mint::$mint<f32>;
+            }
+        };
+    }
+
+    mint_vector!(Vec2, Vector2, x, y);
+    mint_vector!(Vec3, Vector3, x, y, z);
+    mint_vector!(Vec4, Vector4, x, y, z, w);
+
+    impl From<mint::Quaternion<f32>> for Quat {
+        fn from(q: mint::Quaternion<f32>) -> Self {
+            Self::new(q.v.x, q.v.y, q.v.z, q.s)
+        }
+    }
+
+    impl From<Quat> for mint::Quaternion<f32> {
+        fn from(q: Quat) -> Self {
+            Self {
+                v: mint::Vector3 {
+                    x: q.x,
+                    y: q.y,
+                    z: q.z,
+                },
+                s: q.w,
+            }
+        }
+    }
+
+    impl mint::IntoMint for Quat {
+        type MintType = mint::Quaternion<f32>;
+    }
+
+    impl From<mint::ColumnMatrix3<f32>> for Mat3 {
+        fn from(m: mint::ColumnMatrix3<f32>) -> Self {
+            Self::from_cols(m.x.into(), m.y.into(), m.z.into())
+        }
+    }
+
+    impl From<Mat3> for mint::ColumnMatrix3<f32> {
+        fn from(m: Mat3) -> Self {
+            Self {
+                x: m.col(0).into(),
+                y: m.col(1).into(),
+                z: m.col(2).into(),
+            }
+        }
+    }
+
+    impl mint::IntoMint for Mat3 {
+        type MintType = mint::ColumnMatrix3<f32>;
+    }
+
+    impl From<mint::ColumnMatrix4<f32>> for Mat4 {
+        fn from(m: mint::ColumnMatrix4<f32>) -> Self {
+            Self::from_cols(m.x.into(), m.y.into(), m.z.into(), m.w.into())
+        }
+    }
+
+    impl From<Mat4> for mint::ColumnMatrix4<f32> {
+        fn from(m: Mat4) -> Self {
+            Self {
+                x: m.col(0).into(),
+                y: m.col(1).into(),
+                z: m.col(2).into(),
+                w: m.col(3).into(),
+            }
+        }
+    }
+
+    impl mint::IntoMint for Mat4 {
+        type MintType = mint::ColumnMatrix4<f32>;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::f32::consts::{FRAC_PI_2, FRAC_PI_3, PI};
+
+    fn assert_close(a: &[f32], b: &[f32]) {
+        assert!(
+            a.iter().zip(b).all(|(a, b)| (a - b).abs() < 1e-4),
+            "{:?} != {:?}",
+            a,
+            b
+        );
+    }
+
+    fn assert_vec3(a: Vec3, b: Vec3) {
+        assert_close(&a.to_array(), &b.to_array());
+    }
+
+    /// Equal up to the sign, q and -q are the same rotation.
+    fn assert_rotation(a: Quat, b: Quat) {
+        let b = if a.dot(b) < 0.0 {
+            Quat::new(-b.x, -b.y, -b.z, -b.w)
+        } else {
+            b
+        };
+        assert_close(&[a.x, a.y, a.z, a.w], &[b.x, b.y, b.z, b.w]);
+    }
+
+    fn rotations() -> [Quat; 5] {
+        [
+            Quat::IDENTITY,
+            Quat::from_axis_angle(Vec3::new(1.0, 0.0, 0.0), FRAC_PI_2),
+            Quat::from_axis_angle(Vec3::new(0.0, 1.0, 0.0), PI),
+            Quat::from_axis_angle(Vec3::new(0.0, 0.0, 1.0), -FRAC_PI_3),
+            Quat::from_axis_angle(Vec3::new(1.0, 2.0, -3.0).normalize(), 2.5),
+        ]
+    }
+
+    #[test]
+    fn inverse_undoes_the_matrix() {
+        let matrices = [
+            Mat4::IDENTITY,
+            Mat4::translation(Vec3::new(1.0, -2.0, 3.0))
+                * Mat4::from_quat(rotations()[4])
+                * Mat4::scale(Vec3::new(2.0, 0.5, 4.0)),
+            Mat4::perspective_for(ClipSpace::ZeroToOne, FRAC_PI_3, 1.5, 0.1, 100.0),
+            Mat4::look_at(
+                Vec3::new(3.0, 4.0, 5.0),
+                Vec3::ZERO,
+                Vec3::new(0.0, 1.0, 0.0),
+            ),
+        ];
+        for m in matrices {
+            let inverse = m.inverse().unwrap();
+            assert_close(&(m * inverse).m, &Mat4::IDENTITY.m);
+            assert_close(&(inverse * m).m, &Mat4::IDENTITY.m);
+        }
+        assert_eq!(Mat4::scale(Vec3::new(1.0, 0.0, 1.0)).inverse(), None);
+    }
+
+    #[test]
+    fn perspective_maps_the_frustum_to_clip_space() {
+        let (near, far) = (0.5, 50.0);
+        for (clip, near_z, y_sign) in [
+            (ClipSpace::NegativeOneToOne, -1.0, 1.0),
+            (ClipSpace::ZeroToOne, 0.0, 1.0),
+            (ClipSpace::ZeroToOneYDown, 0.0, -1.0),
+        ] {
+            let m = Mat4::perspective_for(clip, FRAC_PI_2, 2.0, near, far);
+            // With a 90° field of view the top of the frustum rises as fast as it recedes
+            let top = m.transform_point(Vec3::new(0.0, near, -near));
+            assert_vec3(top, Vec3::new(0.0, y_sign, near_z));
+            let right = m.transform_point(Vec3::new(2.0 * far, 0.0, -far));
+            assert_vec3(right, Vec3::new(1.0, 0.0, 1.0));
+
+            let frustum = Mat4::frustum_for(clip, -2.0 * near, 2.0 * near, -near, near, near, far);
+            assert_close(&frustum.m, &m.m);
+        }
+    }
+
+    #[test]
+    fn orthographic_maps_the_box_to_clip_space() {
+        for (clip, near_z, y_sign) in [
+            (ClipSpace::NegativeOneToOne, -1.0, 1.0),
+            (ClipSpace::ZeroToOne, 0.0, 1.0),
+            (ClipSpace::ZeroToOneYDown, 0.0, -1.0),
+        ] {
+            let m = Mat4::orthographic_for(clip, -4.0, 2.0, -1.0, 3.0, 1.0, 11.0);
+            let corner = m.transform_point(Vec3::new(-4.0, 3.0, -1.0));
+            assert_vec3(corner, Vec3::new(-1.0, y_sign, near_z));
+            let corner = m.transform_point(Vec3::new(2.0, -1.0, -11.0));
+            assert_vec3(corner, Vec3::new(1.0, -y_sign, 1.0));
+        }
+    }
+
+    #[test]
+    fn look_at_moves_the_eye_to_the_origin() {
+        let eye = Vec3::new(1.0, 2.0, 3.0);
+        let center = Vec3::new(1.0, 2.0, -7.0);
+        let m = Mat4::look_at(eye, center, Vec3::new(0.0, 1.0, 0.0));
+        assert_vec3(m.transform_point(eye), Vec3::ZERO);
+        assert_vec3(m.transform_point(center), Vec3::new(0.0, 0.0, -10.0));
+        assert_vec3(
+            m.transform_point(Vec3::new(2.0, 3.0, 3.0)),
+            Vec3::new(1.0, 1.0, 0.0),
+        );
+
+        // Looking down the x axis turns it into -z, with y still up
+        let m = Mat4::look_at(
+            Vec3::ZERO,
+            Vec3::new(5.0, 0.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+        );
+        assert_vec3(
+            m.transform_vector(Vec3::new(1.0, 0.0, 0.0)),
+            Vec3::new(0.0, 0.0, -1.0),
+        );
+        assert_vec3(
+            m.transform_vector(Vec3::new(0.0, 1.0, 0.0)),
+            Vec3::new(0.0, 1.0, 0.0),
+        );
+    }
+
+    #[test]
+    fn quaternions_round_trip_through_matrices() {
+        let v = Vec3::new(0.3, -1.2, 2.0);
+        for q in rotations() {
+            assert_rotation(Quat::from_mat3(Mat3::from_quat(q)), q);
+            assert_vec3(Mat4::from_quat(q).transform_vector(v), q.rotate(v));
+            assert_vec3(Mat3::from_quat(q) * v, q.rotate(v));
+        }
+        let q = Quat::from_axis_angle(Vec3::new(0.0, 0.0, 1.0), FRAC_PI_2);
+        assert_vec3(q.rotate(Vec3::new(1.0, 0.0, 0.0)), Vec3::new(0.0, 1.0, 0.0));
+        assert_close(&Mat4::from_quat(q).m, &Mat4::rotation_z(FRAC_PI_2).m);
+    }
+
+    #[test]
+    fn slerp_interpolates_between_its_endpoints() {
+        let a = Quat::from_axis_angle(Vec3::new(0.0, 1.0, 0.0), 0.2);
+        let b = Quat::from_axis_angle(Vec3::new(0.0, 1.0, 0.0), 1.4);
+        assert_rotation(a.slerp(b, 0.0), a);
+        assert_rotation(a.slerp(b, 1.0), b);
+        assert_rotation(
+            a.slerp(b, 0.5),
+            Quat::from_axis_angle(Vec3::new(0.0, 1.0, 0.0), 0.8),
+        );
+        // The shortest path from a to -b is the one to b
+        let minus_b = Quat::new(-b.x, -b.y, -b.z, -b.w);
+        assert_rotation(a.slerp(minus_b, 0.5), a.slerp(b, 0.5));
+        // Nearly equal rotations fall back to a normalized lerp
+        assert_rotation(a.slerp(a, 0.5), a);
+    }
+
+    #[test]
+    fn raw_types_share_the_layout() {
+        let m = Mat4::translation(Vec3::new(1.0, 2.0, 3.0));
+        let raw: &kinc_matrix4x4_t = m.as_ref();
+        assert_eq!(raw.m[12..15], [1.0, 2.0, 3.0]);
+        assert_eq!(Mat4::from(Into::<kinc_matrix4x4_t>::into(m)), m);
+        assert_eq!(m[(3, 1)], 2.0);
+    }
+
+    #[cfg(feature = "mint")]
+    #[test]
+    fn mint_conversions_keep_columns() {
+        let m = Mat4::translation(Vec3::new(1.0, 2.0, 3.0)) * Mat4::from_quat(rotations()[4]);
+        let columns: mint::ColumnMatrix4<f32> = m.into();
+        assert_eq!(Vec4::from(columns.w), m.col(3));
+        assert_eq!(Mat4::from(columns), m);
+
+        let m = Mat3::from_quat(rotations()[3]);
+        let columns: mint::ColumnMatrix3<f32> = m.into();
+        assert_eq!(Vec3::from(columns.x), m.col(0));
+        assert_eq!(Mat3::from(columns), m);
+
+        let q = rotations()[4];
+        let mint: mint::Quaternion<f32> = q.into();
+        assert_eq!((mint.v.x, mint.s), (q.x, q.w));
+        assert_eq!(Quat::from(mint), q);
+
+        let v = Vec3::new(1.0, 2.0, 3.0);
+        assert_eq!(Vec3::from(mint::Vector3::from(v)), v);
+    }
+}
@@ -0,0 +1,4 @@
+pub mod http;
+mod socket;
+
+pub use socket::*;
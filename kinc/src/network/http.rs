@@ -0,0 +1,337 @@
+use alloc::{boxed::Box, ffi::CString, string::String, sync::Arc, vec::Vec};
+use core::{
+    ffi::{c_char, c_int, c_void, CStr},
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll, Waker},
+};
+
+use crate::{sys::*, threads::Mutex};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Method {
+    Get,
+    Post,
+    Put,
+    Delete,
+}
+
+impl Into<c_int> for Method {
+    fn into(self) -> c_int {
+        (match self {
+            Method::Get => KINC_HTTP_GET,
+            Method::Post => KINC_HTTP_POST,
+            Method::Put => KINC_HTTP_PUT,
+            Method::Delete => KINC_HTTP_DELETE,
+        }) as c_int
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Response {
+    pub status: i32,
+    /// Kinc hands over the body as a C string without its length,
+    /// so a binary body ends at its first nul byte.
+    pub body: Vec<u8>,
+}
+
+impl Response {
+    /// The body as text, if it is valid UTF-8.
+    pub fn text(&self) -> Result<&str, core::str::Utf8Error> {
+        core::str::from_utf8(&self.body)
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct HttpError {
+    pub code: i32,
+}
+
+impl core::fmt::Display for HttpError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "http request failed with error {}", self.code)
+    }
+}
+
+impl core::error::Error for HttpError {}
+
+pub struct Request<'a> {
+    method: Method,
+    host: &'a str,
+    path: &'a str,
+    port: i32,
+    secure: bool,
+    headers: Vec<(&'a str, &'a str)>,
+    body: Option<&'a str>,
+}
+
+impl<'a> Request<'a> {
+    /// `host` is the bare host name (eg. `example.com`), `path` the path on the server (eg. `/scores`).
+    pub fn new(method: Method, host: &'a str, path: &'a str) -> Self {
+        Self {
+            method,
+            host,
+            path,
+            port: 80,
+            secure: false,
+            headers: Vec::new(),
+            body: None,
+        }
+    }
+
+    pub fn get(host: &'a str, path: &'a str) -> Self {
+        Self::new(Method::Get, host, path)
+    }
+
+    pub fn post(host: &'a str, path: &'a str) -> Self {
+        Self::new(Method::Post, host, path)
+    }
+
+    pub fn put(host: &'a str, path: &'a str) -> Self {
+        Self::new(Method::Put, host, path)
+    }
+
+    pub fn delete(host: &'a str, path: &'a str) -> Self {
+        Self::new(Method::Delete, host, path)
+    }
+
+    pub fn port(mut self, port: i32) -> Self {
+        self.port = port;
+        self
+    }
+
+    /// Uses https, this also switches to port 443 if no other port has been set.
+    pub fn secure(mut self, secure: bool) -> Self {
+        if self.port == 80 && secure {
+            self.port = 443;
+        }
+        self.secure = secure;
+        self
+    }
+
+    pub fn header(mut self, name: &'a str, value: &'a str) -> Self {
+        self.headers.push((name, value));
+        self
+    }
+
+    pub fn body(mut self, body: &'a str) -> Self {
+        self.body = Some(body);
+        self
+    }
+
+    /// Starts the request.
+    ///
+    /// The state shared with Kinc's completion callback is only freed by that callback,
+    /// so it is leaked if Kinc never completes the request (eg. when the application quits first).
+    ///
+    /// # Panics
+    /// If any of the strings contain a nul byte.
+    pub fn send(self) -> Pending {
+        let host = CString::new(self.host).expect("host contains a nul byte");
+        let path = CString::new(self.path).expect("path contains a nul byte");
+        let body = self
+            .body
+            .map(|body| CString::new(body).expect("body contains a nul byte"));
+        let mut header = String::new();
+        for (name, value) in self.headers {
+            header.push_str(name);
+            header.push_str(": ");
+            header.push_str(value);
+            header.push_str("\r\n");
+        }
+        let header = CString::new(header).expect("header contains a nul byte");
+
+        let state = Arc::new(Mutex::new(State {
+            result: None,
+            waker: None,
+        }));
+        // Safety: all strings are valid for the duration of the call, Kinc copies what it needs.
+        // The callback takes back the reference to `state` that's leaked here.
+        unsafe {
+            kinc_http_request(
+                host.as_ptr(),
+                path.as_ptr(),
+                body.as_ref()
+                    .map_or(core::ptr::null(), |body| body.as_ptr()),
+                self.port,
+                self.secure,
+                self.method.into(),
+                if header.is_empty() {
+                    core::ptr::null()
+                } else {
+                    header.as_ptr()
+                },
+                Some(_http_cb),
+                Arc::into_raw(state.clone()).cast_mut().cast(),
+            );
+        }
+        Pending { state }
+    }
+}
+
+struct State {
+    result: Option<Result<Response, HttpError>>,
+    waker: Option<Waker>,
+}
+
+extern "C" fn _http_cb(error: c_int, response: c_int, body: *const c_char, data: *mut c_void) {
+    // Safety: data is the reference leaked in Request::send, Kinc calls this callback exactly once
+    let state = unsafe { Arc::from_raw(data.cast_const().cast::<Mutex<State>>()) };
+    let result = if error != 0 {
+        Err(HttpError { code: error })
+    } else {
+        let body = if body.is_null() {
+            Vec::new()
+        } else {
+            // Safety: body is a valid C string for the duration of the callback
+            unsafe { CStr::from_ptr(body) }.to_bytes().to_vec()
+        };
+        Ok(Response {
+            status: response,
+            body,
+        })
+    };
+    let mut state = state.lock();
+    state.result = Some(result);
+    if let Some(waker) = state.waker.take() {
+        waker.wake();
+    }
+}
+
+/// A request in flight.
+///
+/// Either poll it with [`Pending::take`] (eg. from [`Callbacks::update`](crate::Callbacks::update)),
+/// or `.await` it.
+pub struct Pending {
+    state: Arc<Mutex<State>>,
+}
+
+impl Pending {
+    /// Returns the result once the request has completed.
+    pub fn take(&mut self) -> Option<Result<Response, HttpError>> {
+        self.state.lock().result.take()
+    }
+}
+
+impl Future for Pending {
+    type Output = Result<Response, HttpError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut state = self.state.lock();
+        match state.result.take() {
+            Some(result) => Poll::Ready(result),
+            None => {
+                state.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+type Callback = Box<dyn FnOnce(Result<Response, HttpError>)>;
+
+/// A set of requests whose completion callbacks are called from [`Requests::update`],
+/// which is meant to be called on the main thread once per frame.
+#[derive(Default)]
+pub struct Requests {
+    pending: Vec<(Pending, Callback)>,
+}
+
+impl Requests {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn send(
+        &mut self,
+        request: Request<'_>,
+        callback: impl FnOnce(Result<Response, HttpError>) + 'static,
+    ) {
+        self.pending.push((request.send(), Box::new(callback)));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    pub fn update(&mut self) {
+        let mut i = 0;
+        while i < self.pending.len() {
+            if let Some(result) = self.pending[i].0.take() {
+                let (_, callback) = self.pending.swap_remove(i);
+                callback(result);
+            } else {
+                i += 1;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{
+        io::{Read, Write},
+        net::TcpListener,
+        thread,
+        time::{Duration, Instant},
+    };
+
+    /// Answers one request with `body` and returns what the client sent.
+    fn serve_once(listener: TcpListener, body: &'static [u8]) -> thread::JoinHandle<String> {
+        thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = Vec::new();
+            let mut buffer = [0; 1024];
+            while !request.ends_with(b"\r\n\r\n") {
+                let read = stream.read(&mut buffer).unwrap();
+                if read == 0 {
+                    break;
+                }
+                request.extend_from_slice(&buffer[..read]);
+            }
+            write!(
+                stream,
+                "HTTP/1.1 201 Created\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                body.len()
+            )
+            .unwrap();
+            stream.write_all(body).unwrap();
+            String::from_utf8(request).unwrap()
+        })
+    }
+
+    fn wait(mut pending: Pending) -> Result<Response, HttpError> {
+        let deadline = Instant::now() + Duration::from_secs(10);
+        loop {
+            if let Some(result) = pending.take() {
+                return result;
+            }
+            assert!(Instant::now() < deadline, "the request didn't complete");
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    #[test]
+    fn loopback_request() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        // Not valid UTF-8, which has to come through unchanged
+        let server = serve_once(listener, b"caf\xe9 \xff");
+
+        let response = wait(
+            Request::get("127.0.0.1", "/scores")
+                .port(port.into())
+                .header("X-Test", "1")
+                .send(),
+        )
+        .unwrap();
+        assert_eq!(response.status, 201);
+        assert_eq!(response.body, b"caf\xe9 \xff");
+        assert!(response.text().is_err());
+
+        let request = server.join().unwrap();
+        assert!(request.starts_with("GET /scores HTTP/1."), "{}", request);
+        assert!(request.contains("X-Test: 1\r\n"), "{}", request);
+    }
+}
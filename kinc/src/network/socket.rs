@@ -0,0 +1,367 @@
+use alloc::{ffi::CString, string::ToString};
+use core::{
+    cell::UnsafeCell,
+    mem::MaybeUninit,
+    net::{IpAddr, Ipv4Addr, SocketAddr, SocketAddrV4},
+};
+
+use crate::{sys::*, GetRaw};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Protocol {
+    Udp,
+    Tcp,
+}
+
+impl Into<kinc_socket_protocol_t> for Protocol {
+    fn into(self) -> kinc_socket_protocol_t {
+        match self {
+            Protocol::Udp => kinc_socket_protocol_KINC_SOCKET_PROTOCOL_UDP,
+            Protocol::Tcp => kinc_socket_protocol_KINC_SOCKET_PROTOCOL_TCP,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default)]
+pub struct SocketOptions {
+    pub non_blocking: bool,
+    pub broadcast: bool,
+    pub tcp_no_delay: bool,
+}
+
+impl Into<kinc_socket_options_t> for SocketOptions {
+    fn into(self) -> kinc_socket_options_t {
+        kinc_socket_options_t {
+            non_blocking: self.non_blocking,
+            broadcast: self.broadcast,
+            tcp_no_delay: self.tcp_no_delay,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SocketError {
+    /// The socket could not be created.
+    Open,
+    /// The address could not be resolved.
+    Address,
+    Bind,
+    Listen,
+    Accept,
+    Connect,
+    Send,
+    Receive,
+    /// The socket is non-blocking and the operation would have blocked.
+    WouldBlock,
+}
+
+impl core::fmt::Display for SocketError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            SocketError::Open => write!(f, "could not open socket"),
+            SocketError::Address => write!(f, "could not resolve address"),
+            SocketError::Bind => write!(f, "could not bind socket"),
+            SocketError::Listen => write!(f, "could not listen on socket"),
+            SocketError::Accept => write!(f, "could not accept connection"),
+            SocketError::Connect => write!(f, "could not connect socket"),
+            SocketError::Send => write!(f, "could not send data"),
+            SocketError::Receive => write!(f, "could not receive data"),
+            SocketError::WouldBlock => write!(f, "operation would block"),
+        }
+    }
+}
+
+impl core::error::Error for SocketError {}
+
+/// A TCP or UDP socket.
+///
+/// Kinc only reports IPv4 peer addresses, so [`Socket::accept`] and [`Socket::receive_from`]
+/// return [`SocketAddrV4`]s, while IPv6 addresses can be used to connect, bind and send.
+pub struct Socket {
+    socket: UnsafeCell<kinc_socket_t>,
+    options: SocketOptions,
+}
+
+// Safety: the socket is a plain OS handle
+unsafe impl Send for Socket {}
+
+impl Socket {
+    fn new(
+        address: SocketAddr,
+        protocol: Protocol,
+        options: SocketOptions,
+    ) -> Result<Self, SocketError> {
+        // Safety: usage of zeroed() + the kinc init function should be sufficient to initialize the socket
+        let this = unsafe {
+            let mut socket = MaybeUninit::zeroed();
+            kinc_socket_init(socket.as_mut_ptr());
+            Self {
+                socket: UnsafeCell::new(socket.assume_init()),
+                options,
+            }
+        };
+        let host = CString::new(address.ip().to_string()).unwrap();
+        let family = match address {
+            SocketAddr::V4(_) => kinc_socket_family_KINC_SOCKET_FAMILY_IP4,
+            SocketAddr::V6(_) => kinc_socket_family_KINC_SOCKET_FAMILY_IP6,
+        };
+        // Safety: the socket is initialized, host is a valid C string
+        unsafe {
+            if !kinc_socket_set(
+                this.get_raw(),
+                host.as_ptr(),
+                address.port().into(),
+                family,
+                protocol.into(),
+            ) {
+                return Err(SocketError::Address);
+            }
+            if !kinc_socket_open(this.get_raw(), &mut options.into()) {
+                return Err(SocketError::Open);
+            }
+        }
+        Ok(this)
+    }
+
+    /// Creates a TCP socket listening for connections on `address`.
+    pub fn listen(
+        address: SocketAddr,
+        backlog: i32,
+        options: SocketOptions,
+    ) -> Result<Self, SocketError> {
+        let this = Self::new(address, Protocol::Tcp, options)?;
+        // Safety: the socket is open
+        unsafe {
+            if !kinc_socket_bind(this.get_raw()) {
+                return Err(SocketError::Bind);
+            }
+            if !kinc_socket_listen(this.get_raw(), backlog) {
+                return Err(SocketError::Listen);
+            }
+        }
+        Ok(this)
+    }
+
+    /// Opens a TCP connection to `address`.
+    pub fn connect(address: SocketAddr, options: SocketOptions) -> Result<Self, SocketError> {
+        let this = Self::new(address, Protocol::Tcp, options)?;
+        // Safety: the socket is open
+        if unsafe { kinc_socket_connect(this.get_raw()) } {
+            Ok(this)
+        } else {
+            Err(SocketError::Connect)
+        }
+    }
+
+    /// Creates a UDP socket bound to `address`.
+    pub fn bind(address: SocketAddr, options: SocketOptions) -> Result<Self, SocketError> {
+        let this = Self::new(address, Protocol::Udp, options)?;
+        // Safety: the socket is open
+        if unsafe { kinc_socket_bind(this.get_raw()) } {
+            Ok(this)
+        } else {
+            Err(SocketError::Bind)
+        }
+    }
+
+    pub fn is_non_blocking(&self) -> bool {
+        self.options.non_blocking
+    }
+
+    /// Turns the result of a `kinc_rust_socket_*` call into a byte count.
+    fn check(result: i32, error: SocketError) -> Result<usize, SocketError> {
+        match result {
+            KINC_RUST_SOCKET_WOULD_BLOCK => Err(SocketError::WouldBlock),
+            result if result < 0 => Err(error),
+            result => Ok(result as usize),
+        }
+    }
+
+    /// Accepts a connection on a listening TCP socket.
+    pub fn accept(&self) -> Result<(Socket, SocketAddrV4), SocketError> {
+        let mut address = 0;
+        let mut port = 0;
+        // Safety: the socket is open, and the new socket gets initialized by kinc_socket_accept
+        unsafe {
+            let mut socket = MaybeUninit::zeroed();
+            kinc_socket_init(socket.as_mut_ptr());
+            let socket = Socket {
+                socket: UnsafeCell::new(socket.assume_init()),
+                options: self.options,
+            };
+            Self::check(
+                kinc_rust_socket_accept(self.get_raw(), socket.get_raw(), &mut address, &mut port),
+                SocketError::Accept,
+            )?;
+            Ok((
+                socket,
+                SocketAddrV4::new(Ipv4Addr::from(address), port as u16),
+            ))
+        }
+    }
+
+    /// Sends data over a connected TCP socket.
+    pub fn send(&self, data: &[u8]) -> Result<usize, SocketError> {
+        // Safety: the socket is open, data is valid for data.len() bytes
+        let sent = unsafe {
+            kinc_rust_socket_send_connected(
+                self.get_raw(),
+                data.as_ptr(),
+                data.len().try_into().unwrap_or(i32::MAX),
+            )
+        };
+        Self::check(sent, SocketError::Send)
+    }
+
+    /// Sends a UDP datagram to `address`.
+    pub fn send_to(&self, data: &[u8], address: SocketAddr) -> Result<usize, SocketError> {
+        let size = data.len().try_into().unwrap_or(i32::MAX);
+        // Safety: the socket is open, data is valid for size bytes
+        let sent = unsafe {
+            match address {
+                SocketAddr::V4(address) => kinc_rust_socket_send_address(
+                    self.get_raw(),
+                    (*address.ip()).into(),
+                    address.port().into(),
+                    data.as_ptr(),
+                    size,
+                ),
+                SocketAddr::V6(address) => {
+                    let host = CString::new(IpAddr::from(*address.ip()).to_string()).unwrap();
+                    kinc_rust_socket_send_url(
+                        self.get_raw(),
+                        host.as_ptr(),
+                        address.port().into(),
+                        data.as_ptr(),
+                        size,
+                    )
+                }
+            }
+        };
+        Self::check(sent, SocketError::Send)
+    }
+
+    /// Receives data from a connected TCP socket.
+    ///
+    /// Returns `Ok(0)` once the peer has closed the connection,
+    /// a non-blocking socket returns [`SocketError::WouldBlock`] if no data is available.
+    pub fn receive(&self, buf: &mut [u8]) -> Result<usize, SocketError> {
+        // Safety: the socket is open, buf is valid for buf.len() bytes
+        let received = unsafe {
+            kinc_rust_socket_receive_connected(
+                self.get_raw(),
+                buf.as_mut_ptr(),
+                buf.len().try_into().unwrap_or(i32::MAX),
+            )
+        };
+        Self::check(received, SocketError::Receive)
+    }
+
+    /// Receives a UDP datagram.
+    ///
+    /// A non-blocking socket returns [`SocketError::WouldBlock`] if no datagram is available.
+    pub fn receive_from(&self, buf: &mut [u8]) -> Result<(usize, SocketAddrV4), SocketError> {
+        let mut address = 0;
+        let mut port = 0;
+        // Safety: the socket is open, buf is valid for buf.len() bytes
+        let received = unsafe {
+            kinc_rust_socket_receive(
+                self.get_raw(),
+                buf.as_mut_ptr(),
+                buf.len().try_into().unwrap_or(i32::MAX),
+                &mut address,
+                &mut port,
+            )
+        };
+        let received = Self::check(received, SocketError::Receive)?;
+        Ok((
+            received,
+            SocketAddrV4::new(Ipv4Addr::from(address), port as u16),
+        ))
+    }
+}
+
+impl GetRaw<kinc_socket_t> for Socket {
+    fn get_raw(&self) -> *mut kinc_socket_t {
+        self.socket.get()
+    }
+}
+
+impl Drop for Socket {
+    fn drop(&mut self) {
+        // Safety: self.get_raw is a valid pointer to an initialized socket
+        unsafe { kinc_socket_destroy(self.get_raw()) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A port that was free a moment ago, Kinc can't report the port it bound to.
+    fn free_port() -> u16 {
+        std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port()
+    }
+
+    fn localhost(port: u16) -> SocketAddr {
+        SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::LOCALHOST, port))
+    }
+
+    #[test]
+    fn tcp_round_trip() {
+        let address = localhost(free_port());
+        let listener = Socket::listen(address, 1, SocketOptions::default()).unwrap();
+        let client = Socket::connect(address, SocketOptions::default()).unwrap();
+        let (server, peer) = listener.accept().unwrap();
+        assert_eq!(*peer.ip(), Ipv4Addr::LOCALHOST);
+
+        assert_eq!(client.send(b"hello"), Ok(5));
+        let mut buf = [0; 16];
+        let received = server.receive(&mut buf).unwrap();
+        assert_eq!(&buf[..received], b"hello");
+
+        drop(client);
+        assert_eq!(server.receive(&mut buf), Ok(0));
+    }
+
+    #[test]
+    fn non_blocking_receive_would_block() {
+        let options = SocketOptions {
+            non_blocking: true,
+            ..Default::default()
+        };
+        let socket = Socket::bind(localhost(free_port()), options).unwrap();
+        let mut buf = [0; 16];
+        assert_eq!(socket.receive_from(&mut buf), Err(SocketError::WouldBlock));
+    }
+
+    #[test]
+    fn only_would_block_results_become_would_block() {
+        let check = |result| Socket::check(result, SocketError::Receive);
+        assert_eq!(check(12), Ok(12));
+        assert_eq!(check(0), Ok(0));
+        assert_eq!(check(-1), Err(SocketError::Receive));
+        assert_eq!(
+            check(KINC_RUST_SOCKET_WOULD_BLOCK),
+            Err(SocketError::WouldBlock)
+        );
+    }
+
+    #[test]
+    fn udp_round_trip() {
+        let a = localhost(free_port());
+        let b = localhost(free_port());
+        let first = Socket::bind(a, SocketOptions::default()).unwrap();
+        let second = Socket::bind(b, SocketOptions::default()).unwrap();
+
+        assert_eq!(first.send_to(b"ping", b), Ok(4));
+        let mut buf = [0; 16];
+        let (received, from) = second.receive_from(&mut buf).unwrap();
+        assert_eq!(&buf[..received], b"ping");
+        assert_eq!(SocketAddr::V4(from), a);
+    }
+}
@@ -0,0 +1,206 @@
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use crate::sys::*;
+
+static ALIVE: AtomicBool = AtomicBool::new(false);
+
+/// Kinc's random number generator, which produces the same sequences as Kinc's C code for the same seed.
+///
+/// Kinc keeps the generator state in a global, so only one `Random` can exist at a time.
+pub struct Random {
+    // The generator state is global, so it must not be shared between threads.
+    _not_send: core::marker::PhantomData<*mut ()>,
+}
+
+impl Random {
+    /// # Panics
+    /// Panics if another `Random` is still alive.
+    pub fn new(seed: i64) -> Self {
+        Self::try_new(seed).expect("only one Random can exist at a time")
+    }
+
+    /// Returns `None` if another `Random` is still alive.
+    pub fn try_new(seed: i64) -> Option<Self> {
+        if ALIVE.swap(true, Ordering::Acquire) {
+            return None;
+        }
+        // Safety: we hold the only handle to the generator
+        unsafe { kinc_random_init(seed) };
+        Some(Self {
+            _not_send: core::marker::PhantomData,
+        })
+    }
+
+    /// Resets the generator, after which it repeats the sequence of a new `Random` with the same seed.
+    pub fn reseed(&mut self, seed: i64) {
+        // Safety: we hold the only handle to the generator
+        unsafe { kinc_random_init(seed) }
+    }
+
+    pub fn get(&mut self) -> i64 {
+        // Safety: the generator has been initialized
+        unsafe { kinc_random_get() }
+    }
+
+    /// Returns a number between 0 and `max`, inclusive.
+    ///
+    /// # Panics
+    /// If `max` is negative.
+    pub fn get_max(&mut self, max: i64) -> i64 {
+        self.get_in(0, max)
+    }
+
+    /// Returns a number between `min` and `max`, inclusive.
+    ///
+    /// The number is mapped from one [`Random::get`] here instead of by `kinc_random_get_in`,
+    /// so that it stays in range for any bounds, including ranges wider than `i64::MAX`.
+    ///
+    /// # Panics
+    /// If `min` is greater than `max`.
+    pub fn get_in(&mut self, min: i64, max: i64) -> i64 {
+        assert!(min <= max, "empty range {}..={}", min, max);
+        let value = self.get() as u64;
+        match (max.wrapping_sub(min) as u64).checked_add(1) {
+            Some(count) => min.wrapping_add((value % count) as i64),
+            None => value as i64,
+        }
+    }
+}
+
+impl Drop for Random {
+    fn drop(&mut self) {
+        ALIVE.store(false, Ordering::Release);
+    }
+}
+
+#[cfg(feature = "rand_core")]
+impl rand_core::RngCore for Random {
+    /// The upper half of [`RngCore::next_u64`](rand_core::RngCore::next_u64),
+    /// Kinc's generator produces full 64-bit numbers, so those bits are as random as the lower ones.
+    fn next_u32(&mut self) -> u32 {
+        (self.get() as u64 >> 32) as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.get() as u64
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        rand_core::impls::fill_bytes_via_next(self, dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Mutex, MutexGuard, PoisonError};
+
+    // Kinc's generator is global, so the tests take turns
+    static GENERATOR: Mutex<()> = Mutex::new(());
+
+    fn generator() -> MutexGuard<'static, ()> {
+        // A test that is expected to panic poisons the lock
+        GENERATOR.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// SplitMix64, to draw test inputs without touching Kinc's generator.
+    fn inputs(mut state: u64) -> impl FnMut() -> i64 {
+        move || {
+            state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+            let mut z = state;
+            z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+            (z ^ (z >> 31)) as i64
+        }
+    }
+
+    #[test]
+    fn equal_seeds_give_equal_sequences() {
+        let _generator = generator();
+        let mut next = inputs(1);
+        let seeds = [0, 1, -1, i64::MIN, i64::MAX]
+            .into_iter()
+            .chain((0..300).map(|_| next()));
+        let mut random = Random::new(0);
+        assert!(Random::try_new(0).is_none());
+        for seed in seeds {
+            random.reseed(seed);
+            let first: Vec<i64> = (0..32).map(|_| random.get()).collect();
+            drop(random);
+
+            random = Random::new(seed);
+            let second: Vec<i64> = (0..32).map(|_| random.get()).collect();
+            assert_eq!(first, second, "seed {}", seed);
+        }
+
+        // Not a property of every generator, but two seeds colliding would hint at a broken seeding
+        random.reseed(1);
+        let one = random.get();
+        random.reseed(2);
+        assert_ne!(one, random.get());
+    }
+
+    #[test]
+    fn ranges_hold_their_bounds() {
+        let _generator = generator();
+        let mut next = inputs(2);
+        let mut random = Random::new(next());
+        let mut check = |min: i64, max: i64| {
+            for _ in 0..16 {
+                let value = random.get_in(min, max);
+                assert!(
+                    (min..=max).contains(&value),
+                    "{} in {}..={}",
+                    value,
+                    min,
+                    max
+                );
+            }
+            let value = random.get_max(max.max(0));
+            assert!((0..=max.max(0)).contains(&value));
+        };
+        for (min, max) in [
+            (0, 0),
+            (-5, 5),
+            (i64::MIN, i64::MAX),
+            (i64::MIN, 0),
+            (-1, i64::MAX),
+        ] {
+            check(min, max);
+        }
+        for _ in 0..300 {
+            let (a, b) = (next(), next());
+            check(a.min(b), a.max(b));
+            // Narrow ranges as well, arbitrary pairs are almost always wider than a few values
+            let small = (b % 16).abs();
+            check(a.min(a.wrapping_add(small)), a.max(a.wrapping_add(small)));
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "empty range")]
+    fn inverted_ranges_panic() {
+        let _generator = generator();
+        Random::new(0).get_in(1, 0);
+    }
+
+    #[test]
+    fn every_bit_varies() {
+        let _generator = generator();
+        let mut random = Random::new(42);
+        let (mut ones, mut zeros) = (0_u64, 0_u64);
+        for _ in 0..256 {
+            let value = random.get() as u64;
+            ones |= value;
+            zeros |= !value;
+        }
+        // next_u32 takes the upper half, so those bits in particular must not be stuck
+        assert_eq!(ones, u64::MAX);
+        assert_eq!(zeros, u64::MAX);
+    }
+}
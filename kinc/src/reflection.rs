@@ -0,0 +1,232 @@
+//! What a compiled shader expects, produced by [`compile_shader!`](crate::compile_shader)
+//! with `reflect = true`:
+//!
+//! ```ignore
+//! const VERTEX: ReflectedShader = kinc::compile_shader!(vertex, "...", reflect = true);
+//! ```
+//!
+//! The data comes from compiling the shader to SPIR-V, whatever the graphics backend is.
+
+use core::ffi::CStr;
+
+use crate::g4::{ConstantLocation, Pipeline, TextureUnit, VertexData, VertexStructure};
+
+/// The type of a shader variable.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DataType {
+    Bool,
+    Int,
+    IVec2,
+    IVec3,
+    IVec4,
+    UInt,
+    UVec2,
+    UVec3,
+    UVec4,
+    Float,
+    Vec2,
+    Vec3,
+    Vec4,
+    Mat2,
+    Mat3,
+    Mat4,
+    Sampler2D,
+    Sampler2DArray,
+    Sampler3D,
+    SamplerCube,
+    Struct,
+    /// A type without a counterpart in Kinc, like double precision vectors.
+    Other,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Attribute {
+    pub name: &'static CStr,
+    pub location: u32,
+    pub ty: DataType,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Uniform {
+    pub name: &'static CStr,
+    pub ty: DataType,
+    /// The array length, 1 for uniforms that aren't arrays.
+    pub count: u32,
+}
+
+impl Uniform {
+    pub fn location(&self, pipeline: &Pipeline) -> ConstantLocation {
+        pipeline.get_constant_location(self.name)
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Sampler {
+    pub name: &'static CStr,
+    pub binding: Option<u32>,
+    pub ty: DataType,
+}
+
+impl Sampler {
+    pub fn unit(&self, pipeline: &Pipeline) -> TextureUnit {
+        pipeline.get_texture_unit(self.name)
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Reflection {
+    /// The inputs of the stage without built-ins, sorted by location,
+    /// for vertex shaders these are the vertex attributes.
+    pub attributes: &'static [Attribute],
+    /// Loose uniforms and the members of uniform blocks, in declaration order.
+    pub uniforms: &'static [Uniform],
+    /// Sorted by binding.
+    pub samplers: &'static [Sampler],
+    /// The local size of compute shaders.
+    pub workgroup_size: Option<[u32; 3]>,
+}
+
+impl Reflection {
+    pub fn attribute(&self, name: &str) -> Option<&'static Attribute> {
+        self.attributes
+            .iter()
+            .find(|attribute| attribute.name.to_bytes() == name.as_bytes())
+    }
+
+    pub fn uniform(&self, name: &str) -> Option<&'static Uniform> {
+        self.uniforms
+            .iter()
+            .find(|uniform| uniform.name.to_bytes() == name.as_bytes())
+    }
+
+    pub fn sampler(&self, name: &str) -> Option<&'static Sampler> {
+        self.samplers
+            .iter()
+            .find(|sampler| sampler.name.to_bytes() == name.as_bytes())
+    }
+
+    /// Checks that `structure` provides every attribute with a fitting data type.
+    ///
+    /// Float attributes accept float and integer data with at most as many components,
+    /// the missing ones are filled in by the GPU. Integer attributes need non-normalized
+    /// integer data of the same signedness.
+    pub fn validate(&self, structure: &VertexStructure) -> Result<(), ValidationError> {
+        for attribute in self.attributes {
+            let data = structure
+                .elements()
+                .find(|(name, _)| *name == attribute.name)
+                .map(|(_, data)| data)
+                .ok_or(ValidationError::MissingAttribute {
+                    name: attribute.name,
+                })?;
+            if !accepts(attribute.ty, data) {
+                return Err(ValidationError::TypeMismatch {
+                    name: attribute.name,
+                    expected: attribute.ty,
+                    found: data,
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A shader's bytecode for the active graphics backend along with its reflection data.
+#[derive(Copy, Clone, Debug)]
+pub struct ReflectedShader {
+    pub bytecode: &'static [u8],
+    pub reflection: Reflection,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ValidationError {
+    /// The shader reads an attribute that the vertex structure doesn't have.
+    MissingAttribute { name: &'static CStr },
+    TypeMismatch {
+        name: &'static CStr,
+        expected: DataType,
+        found: VertexData,
+    },
+}
+
+impl core::fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ValidationError::MissingAttribute { name } => {
+                write!(f, "the vertex structure has no attribute {:?}", name)
+            }
+            ValidationError::TypeMismatch {
+                name,
+                expected,
+                found,
+            } => write!(
+                f,
+                "attribute {:?} is a {:?} in the shader, but {:?} in the vertex structure",
+                name, expected, found
+            ),
+        }
+    }
+}
+
+impl core::error::Error for ValidationError {}
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum Kind {
+    Float,
+    Signed,
+    Unsigned,
+    Normalized,
+}
+
+/// The kind of a vertex data type and its number of components.
+fn layout(data: VertexData) -> Option<(Kind, u32)> {
+    use VertexData::*;
+    Some(match data {
+        F32_1X => (Kind::Float, 1),
+        F32_2X => (Kind::Float, 2),
+        F32_3X => (Kind::Float, 3),
+        F32_4X => (Kind::Float, 4),
+        I8_1X | I16_1X | I32_1X => (Kind::Signed, 1),
+        I8_2X | I16_2X | I32_2X => (Kind::Signed, 2),
+        I32_3X => (Kind::Signed, 3),
+        I8_4X | I16_4X | I32_4X => (Kind::Signed, 4),
+        U8_1X | U16_1X | U32_1X => (Kind::Unsigned, 1),
+        U8_2X | U16_2X | U32_2X => (Kind::Unsigned, 2),
+        U32_3X => (Kind::Unsigned, 3),
+        U8_4X | U16_4X | U32_4X => (Kind::Unsigned, 4),
+        I8_1xNormalized | U8_1xNormalized | I16_1xNormalized | U16_1xNormalized => {
+            (Kind::Normalized, 1)
+        }
+        I8_2xNormalized | U8_2xNormalized | I16_2xNormalized | U16_2xNormalized => {
+            (Kind::Normalized, 2)
+        }
+        I8_4xNormalized | U8_4xNormalized | I16_4xNormalized | U16_4xNormalized => {
+            (Kind::Normalized, 4)
+        }
+        VertexData::None | F32_4X4 => return Option::None,
+    })
+}
+
+fn accepts(ty: DataType, data: VertexData) -> bool {
+    let (kind, components) = match ty {
+        DataType::Float => (Kind::Float, 1),
+        DataType::Vec2 => (Kind::Float, 2),
+        DataType::Vec3 => (Kind::Float, 3),
+        DataType::Vec4 => (Kind::Float, 4),
+        DataType::Int => (Kind::Signed, 1),
+        DataType::IVec2 => (Kind::Signed, 2),
+        DataType::IVec3 => (Kind::Signed, 3),
+        DataType::IVec4 => (Kind::Signed, 4),
+        DataType::UInt => (Kind::Unsigned, 1),
+        DataType::UVec2 => (Kind::Unsigned, 2),
+        DataType::UVec3 => (Kind::Unsigned, 3),
+        DataType::UVec4 => (Kind::Unsigned, 4),
+        DataType::Mat4 => return data == VertexData::F32_4X4,
+        _ => return false,
+    };
+    match layout(data) {
+        Some((_, data_components)) if kind == Kind::Float => data_components <= components,
+        Some((data_kind, data_components)) => data_kind == kind && data_components == components,
+        None => false,
+    }
+}
@@ -0,0 +1,544 @@
+//! Four-wide float vectors, mirroring `kinc/simd/float32x4.h`.
+//!
+//! Kinc implements its SIMD functions as `static inline` functions in the header, which bindgen can't bind,
+//! so this module uses the same SSE and NEON instructions through `core::arch` and falls back to scalar code
+//! on other targets.
+
+use core::{
+    fmt,
+    ops::{
+        Add, AddAssign, BitAnd, BitOr, BitXor, Div, DivAssign, Mul, MulAssign, Neg, Not, Sub,
+        SubAssign,
+    },
+};
+
+#[cfg(all(target_arch = "aarch64", target_feature = "neon"))]
+mod neon;
+// Also compiled for tests, which compare the SIMD implementations against it
+#[cfg(any(
+    test,
+    not(any(
+        all(
+            any(target_arch = "x86", target_arch = "x86_64"),
+            target_feature = "sse"
+        ),
+        all(target_arch = "aarch64", target_feature = "neon")
+    ))
+))]
+mod scalar;
+#[cfg(all(
+    any(target_arch = "x86", target_arch = "x86_64"),
+    target_feature = "sse"
+))]
+mod sse;
+
+#[cfg(all(target_arch = "aarch64", target_feature = "neon"))]
+use neon as imp;
+#[cfg(not(any(
+    all(
+        any(target_arch = "x86", target_arch = "x86_64"),
+        target_feature = "sse"
+    ),
+    all(target_arch = "aarch64", target_feature = "neon")
+)))]
+use scalar as imp;
+#[cfg(all(
+    any(target_arch = "x86", target_arch = "x86_64"),
+    target_feature = "sse"
+))]
+use sse as imp;
+
+/// Four `f32` lanes, named a, b, c and d by Kinc.
+#[derive(Copy, Clone)]
+#[repr(transparent)]
+pub struct F32x4(imp::F32x4);
+
+/// The result of a lane-wise comparison, each lane is either all ones or all zeros.
+#[derive(Copy, Clone)]
+#[repr(transparent)]
+pub struct Mask32x4(imp::Mask32x4);
+
+impl F32x4 {
+    pub fn new(a: f32, b: f32, c: f32, d: f32) -> Self {
+        Self::from_array([a, b, c, d])
+    }
+
+    pub fn splat(value: f32) -> Self {
+        Self(imp::F32x4::splat(value))
+    }
+
+    pub fn zero() -> Self {
+        Self::splat(0.0)
+    }
+
+    pub fn from_array(values: [f32; 4]) -> Self {
+        Self(imp::F32x4::load(&values))
+    }
+
+    /// Loads the first four values of a slice.
+    ///
+    /// # Panics
+    /// Panics if the slice is shorter than four values.
+    pub fn from_slice(values: &[f32]) -> Self {
+        Self::from_array(values[..4].try_into().unwrap())
+    }
+
+    pub fn to_array(self) -> [f32; 4] {
+        self.0.store()
+    }
+
+    /// Stores the lanes into the first four values of a slice.
+    ///
+    /// # Panics
+    /// Panics if the slice is shorter than four values.
+    pub fn write_to_slice(self, values: &mut [f32]) {
+        values[..4].copy_from_slice(&self.to_array());
+    }
+
+    /// # Panics
+    /// Panics if `index` is larger than 3.
+    pub fn get(self, index: usize) -> f32 {
+        self.to_array()[index]
+    }
+
+    pub fn abs(self) -> Self {
+        Self(self.0.abs())
+    }
+
+    pub fn sqrt(self) -> Self {
+        Self(self.0.sqrt())
+    }
+
+    /// An approximation of `1 / self`, with the precision of the hardware instruction.
+    pub fn reciprocal_approximation(self) -> Self {
+        Self(self.0.recip_approx())
+    }
+
+    /// An approximation of `1 / sqrt(self)`, with the precision of the hardware instruction.
+    pub fn reciprocal_sqrt_approximation(self) -> Self {
+        Self(self.0.rsqrt_approx())
+    }
+
+    /// Lane-wise `if self < other { self } else { other }` like SSE's `minps`,
+    /// so lanes where either value is NaN are taken from `other`.
+    pub fn min(self, other: Self) -> Self {
+        Self(self.0.min(other.0))
+    }
+
+    /// Lane-wise `if self > other { self } else { other }` like SSE's `maxps`,
+    /// so lanes where either value is NaN are taken from `other`.
+    pub fn max(self, other: Self) -> Self {
+        Self(self.0.max(other.0))
+    }
+
+    pub fn cmp_eq(self, other: Self) -> Mask32x4 {
+        Mask32x4(self.0.cmp_eq(other.0))
+    }
+
+    pub fn cmp_ne(self, other: Self) -> Mask32x4 {
+        Mask32x4(self.0.cmp_ne(other.0))
+    }
+
+    pub fn cmp_lt(self, other: Self) -> Mask32x4 {
+        Mask32x4(self.0.cmp_lt(other.0))
+    }
+
+    pub fn cmp_le(self, other: Self) -> Mask32x4 {
+        Mask32x4(self.0.cmp_le(other.0))
+    }
+
+    pub fn cmp_gt(self, other: Self) -> Mask32x4 {
+        Mask32x4(self.0.cmp_gt(other.0))
+    }
+
+    pub fn cmp_ge(self, other: Self) -> Mask32x4 {
+        Mask32x4(self.0.cmp_ge(other.0))
+    }
+
+    /// Picks lanes from `self` where `mask` is set and from `other` where it isn't.
+    pub fn select(self, other: Self, mask: Mask32x4) -> Self {
+        Self(imp::F32x4::select(mask.0, self.0, other.0))
+    }
+
+    /// Returns `[self[left.0], self[left.1], other[right.0], other[right.1]]`.
+    ///
+    /// # Panics
+    /// Panics if an index is larger than 3.
+    pub fn shuffle(self, other: Self, left: (usize, usize), right: (usize, usize)) -> Self {
+        let (l, r) = (self.to_array(), other.to_array());
+        Self::from_array([l[left.0], l[left.1], r[right.0], r[right.1]])
+    }
+
+    /// Interleaves the low halves, `abcd` and `efgh` become `aebf`.
+    pub fn shuffle_aebf(self, other: Self) -> Self {
+        Self(self.0.shuffle_aebf(other.0))
+    }
+
+    /// Interleaves the high halves, `abcd` and `efgh` become `cgdh`.
+    pub fn shuffle_cgdh(self, other: Self) -> Self {
+        Self(self.0.shuffle_cgdh(other.0))
+    }
+
+    /// Concatenates the low halves, `abcd` and `efgh` become `abef`.
+    pub fn shuffle_abef(self, other: Self) -> Self {
+        Self(self.0.shuffle_abef(other.0))
+    }
+
+    /// Concatenates the high halves in reverse, `abcd` and `efgh` become `ghcd`.
+    pub fn shuffle_ghcd(self, other: Self) -> Self {
+        Self(self.0.shuffle_ghcd(other.0))
+    }
+}
+
+impl Default for F32x4 {
+    fn default() -> Self {
+        Self::zero()
+    }
+}
+
+impl fmt::Debug for F32x4 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("F32x4").field(&self.to_array()).finish()
+    }
+}
+
+impl PartialEq for F32x4 {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp_eq(*other).all()
+    }
+}
+
+impl From<[f32; 4]> for F32x4 {
+    fn from(values: [f32; 4]) -> Self {
+        Self::from_array(values)
+    }
+}
+
+impl From<F32x4> for [f32; 4] {
+    fn from(v: F32x4) -> Self {
+        v.to_array()
+    }
+}
+
+macro_rules! binary_op {
+    ($op:ident, $fn:ident, $assign_op:ident, $assign_fn:ident) => {
+        impl $op for F32x4 {
+            type Output = Self;
+            fn $fn(self, rhs: Self) -> Self {
+                Self(self.0.$fn(rhs.0))
+            }
+        }
+
+        impl $op<f32> for F32x4 {
+            type Output = Self;
+            fn $fn(self, rhs: f32) -> Self {
+                self.$fn(Self::splat(rhs))
+            }
+        }
+
+        impl $assign_op for F32x4 {
+            fn $assign_fn(&mut self, rhs: Self) {
+                *self = (*self).$fn(rhs);
+            }
+        }
+
+        impl $assign_op<f32> for F32x4 {
+            fn $assign_fn(&mut self, rhs: f32) {
+                *self = (*self).$fn(rhs);
+            }
+        }
+    };
+}
+
+binary_op!(Add, add, AddAssign, add_assign);
+binary_op!(Sub, sub, SubAssign, sub_assign);
+binary_op!(Mul, mul, MulAssign, mul_assign);
+binary_op!(Div, div, DivAssign, div_assign);
+
+impl Neg for F32x4 {
+    type Output = Self;
+    fn neg(self) -> Self {
+        Self(self.0.neg())
+    }
+}
+
+impl Mask32x4 {
+    pub fn from_array(values: [bool; 4]) -> Self {
+        Self(imp::Mask32x4::from_array(values))
+    }
+
+    pub fn to_array(self) -> [bool; 4] {
+        self.0.to_array()
+    }
+
+    /// Whether all lanes are set.
+    pub fn all(self) -> bool {
+        self.to_array() == [true; 4]
+    }
+
+    /// Whether any lane is set.
+    pub fn any(self) -> bool {
+        self.to_array() != [false; 4]
+    }
+}
+
+impl fmt::Debug for Mask32x4 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Mask32x4").field(&self.to_array()).finish()
+    }
+}
+
+impl PartialEq for Mask32x4 {
+    fn eq(&self, other: &Self) -> bool {
+        self.to_array() == other.to_array()
+    }
+}
+
+impl Eq for Mask32x4 {}
+
+impl BitAnd for Mask32x4 {
+    type Output = Self;
+    fn bitand(self, rhs: Self) -> Self {
+        Self(self.0.and(rhs.0))
+    }
+}
+
+impl BitOr for Mask32x4 {
+    type Output = Self;
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0.or(rhs.0))
+    }
+}
+
+impl BitXor for Mask32x4 {
+    type Output = Self;
+    fn bitxor(self, rhs: Self) -> Self {
+        Self(self.0.xor(rhs.0))
+    }
+}
+
+impl Not for Mask32x4 {
+    type Output = Self;
+    fn not(self) -> Self {
+        Self(self.0.not())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VALUES: [f32; 12] = [
+        0.0,
+        -0.0,
+        1.0,
+        -1.5,
+        0.25,
+        7.0,
+        3e38,
+        -3e38,
+        f32::MIN_POSITIVE,
+        f32::INFINITY,
+        f32::NEG_INFINITY,
+        f32::NAN,
+    ];
+
+    /// Every ordered pair of `VALUES`, four pairs per pair of vectors.
+    fn pairs() -> Vec<([f32; 4], [f32; 4])> {
+        let pairs: Vec<(f32, f32)> = VALUES
+            .iter()
+            .flat_map(|a| VALUES.iter().map(move |b| (*a, *b)))
+            .collect();
+        pairs
+            .chunks_exact(4)
+            .map(|chunk| {
+                (
+                    [0, 1, 2, 3].map(|i| chunk[i].0),
+                    [0, 1, 2, 3].map(|i| chunk[i].1),
+                )
+            })
+            .collect()
+    }
+
+    /// Bitwise equality, except that all NaNs are equal.
+    fn assert_same(simd: [f32; 4], scalar: [f32; 4], operation: &str, inputs: &[[f32; 4]]) {
+        let same = |a: f32, b: f32| a.to_bits() == b.to_bits() || (a.is_nan() && b.is_nan());
+        assert!(
+            (0..4).all(|i| same(simd[i], scalar[i])),
+            "{} of {:?}: {:?} != {:?}",
+            operation,
+            inputs,
+            simd,
+            scalar
+        );
+    }
+
+    /// Within the precision of the coarsest estimate instruction (NEON's, with 8 bits).
+    fn assert_close(simd: [f32; 4], scalar: [f32; 4], operation: &str, input: [f32; 4]) {
+        let close = |a: f32, b: f32| {
+            if b.is_nan() {
+                a.is_nan()
+            } else if b.is_infinite() || b == 0.0 {
+                a == b
+            } else if b.abs() < f32::MIN_POSITIVE {
+                // Estimates may flush denormal results to zero
+                a.abs() < f32::MIN_POSITIVE
+            } else {
+                ((a - b) / b).abs() < 1.0 / 256.0
+            }
+        };
+        assert!(
+            (0..4).all(|i| close(simd[i], scalar[i])),
+            "{} of {:?}: {:?} != {:?}",
+            operation,
+            input,
+            simd,
+            scalar
+        );
+    }
+
+    macro_rules! compare_unary {
+        ($($op:ident),*) => {
+            $(for (a, b) in pairs() {
+                for input in [a, b] {
+                    assert_same(
+                        imp::F32x4::load(&input).$op().store(),
+                        scalar::F32x4::load(&input).$op().store(),
+                        stringify!($op),
+                        &[input],
+                    );
+                }
+            })*
+        };
+    }
+
+    macro_rules! compare_binary {
+        ($($op:ident),*) => {
+            $(for (a, b) in pairs() {
+                assert_same(
+                    imp::F32x4::load(&a).$op(imp::F32x4::load(&b)).store(),
+                    scalar::F32x4::load(&a).$op(scalar::F32x4::load(&b)).store(),
+                    stringify!($op),
+                    &[a, b],
+                );
+            })*
+        };
+    }
+
+    macro_rules! compare_cmp {
+        ($($op:ident),*) => {
+            $(for (a, b) in pairs() {
+                assert_eq!(
+                    imp::F32x4::load(&a).$op(imp::F32x4::load(&b)).to_array(),
+                    scalar::F32x4::load(&a).$op(scalar::F32x4::load(&b)).to_array(),
+                    "{} of {:?} and {:?}",
+                    stringify!($op),
+                    a,
+                    b
+                );
+            })*
+        };
+    }
+
+    #[test]
+    fn arithmetic_matches_scalar() {
+        compare_binary!(add, sub, mul, div, min, max);
+        compare_unary!(neg, abs, sqrt);
+        for (a, _) in pairs() {
+            assert_same(
+                imp::F32x4::splat(a[0]).store(),
+                scalar::F32x4::splat(a[0]).store(),
+                "splat",
+                &[a],
+            );
+            assert_same(imp::F32x4::load(&a).store(), a, "load", &[a]);
+        }
+    }
+
+    #[test]
+    fn estimates_match_scalar() {
+        for (a, b) in pairs() {
+            for input in [a, b] {
+                assert_close(
+                    imp::F32x4::load(&input).recip_approx().store(),
+                    scalar::F32x4::load(&input).recip_approx().store(),
+                    "recip_approx",
+                    input,
+                );
+                // Only positive inputs, negative ones give NaN everywhere
+                let input = input.map(f32::abs);
+                assert_close(
+                    imp::F32x4::load(&input).rsqrt_approx().store(),
+                    scalar::F32x4::load(&input).rsqrt_approx().store(),
+                    "rsqrt_approx",
+                    input,
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn comparisons_match_scalar() {
+        compare_cmp!(cmp_eq, cmp_ne, cmp_lt, cmp_le, cmp_gt, cmp_ge);
+    }
+
+    #[test]
+    fn shuffles_and_select_match_scalar() {
+        compare_binary!(shuffle_aebf, shuffle_cgdh, shuffle_abef, shuffle_ghcd);
+        for (a, b) in pairs() {
+            for bits in 0..16 {
+                let mask = [0, 1, 2, 3].map(|i| bits & (1 << i) != 0);
+                assert_same(
+                    imp::F32x4::select(
+                        imp::Mask32x4::from_array(mask),
+                        imp::F32x4::load(&a),
+                        imp::F32x4::load(&b),
+                    )
+                    .store(),
+                    scalar::F32x4::select(
+                        scalar::Mask32x4::from_array(mask),
+                        scalar::F32x4::load(&a),
+                        scalar::F32x4::load(&b),
+                    )
+                    .store(),
+                    "select",
+                    &[a, b],
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn masks_match_scalar() {
+        let masks = (0..16).map(|bits: u32| [0, 1, 2, 3].map(|i| bits & (1 << i) != 0));
+        for a in masks.clone() {
+            let (simd, scalar) = (
+                imp::Mask32x4::from_array(a),
+                scalar::Mask32x4::from_array(a),
+            );
+            assert_eq!(simd.not().to_array(), scalar.not().to_array());
+            for b in masks.clone() {
+                let (simd_b, scalar_b) = (
+                    imp::Mask32x4::from_array(b),
+                    scalar::Mask32x4::from_array(b),
+                );
+                assert_eq!(simd.and(simd_b).to_array(), scalar.and(scalar_b).to_array());
+                assert_eq!(simd.or(simd_b).to_array(), scalar.or(scalar_b).to_array());
+                assert_eq!(simd.xor(simd_b).to_array(), scalar.xor(scalar_b).to_array());
+            }
+        }
+    }
+
+    #[test]
+    fn min_and_max_take_nan_lanes_from_other() {
+        let nan = F32x4::new(f32::NAN, 1.0, f32::NAN, -0.0);
+        let other = F32x4::new(2.0, f32::NAN, f32::NAN, 0.0);
+        for result in [nan.min(other), nan.max(other)] {
+            let [a, b, c, d] = result.to_array();
+            assert_eq!(a, 2.0);
+            assert!(b.is_nan() && c.is_nan());
+            // -0.0 and 0.0 compare equal, so the second operand wins
+            assert_eq!(d.to_bits(), 0.0_f32.to_bits());
+        }
+    }
+}
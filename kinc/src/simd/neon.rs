@@ -0,0 +1,167 @@
+use core::arch::aarch64::*;
+
+/// Calls a NEON intrinsic, this module is only compiled when NEON is enabled for the target.
+macro_rules! neon {
+    ($e:expr) => {
+        // Safety: NEON is enabled at compile time
+        unsafe { $e }
+    };
+}
+
+#[derive(Copy, Clone)]
+pub(super) struct F32x4(float32x4_t);
+
+#[derive(Copy, Clone)]
+pub(super) struct Mask32x4(uint32x4_t);
+
+impl F32x4 {
+    pub(super) fn splat(value: f32) -> Self {
+        Self(neon!(vdupq_n_f32(value)))
+    }
+
+    pub(super) fn load(values: &[f32; 4]) -> Self {
+        // Safety: values points to four floats
+        Self(unsafe { vld1q_f32(values.as_ptr()) })
+    }
+
+    pub(super) fn store(self) -> [f32; 4] {
+        let mut values = [0.0; 4];
+        // Safety: values has room for four floats
+        unsafe { vst1q_f32(values.as_mut_ptr(), self.0) };
+        values
+    }
+
+    pub(super) fn add(self, other: Self) -> Self {
+        Self(neon!(vaddq_f32(self.0, other.0)))
+    }
+
+    pub(super) fn sub(self, other: Self) -> Self {
+        Self(neon!(vsubq_f32(self.0, other.0)))
+    }
+
+    pub(super) fn mul(self, other: Self) -> Self {
+        Self(neon!(vmulq_f32(self.0, other.0)))
+    }
+
+    pub(super) fn div(self, other: Self) -> Self {
+        Self(neon!(vdivq_f32(self.0, other.0)))
+    }
+
+    pub(super) fn neg(self) -> Self {
+        Self(neon!(vnegq_f32(self.0)))
+    }
+
+    pub(super) fn abs(self) -> Self {
+        Self(neon!(vabsq_f32(self.0)))
+    }
+
+    pub(super) fn sqrt(self) -> Self {
+        Self(neon!(vsqrtq_f32(self.0)))
+    }
+
+    pub(super) fn recip_approx(self) -> Self {
+        Self(neon!(vrecpeq_f32(self.0)))
+    }
+
+    pub(super) fn rsqrt_approx(self) -> Self {
+        Self(neon!(vrsqrteq_f32(self.0)))
+    }
+
+    // vminq_f32 and vmaxq_f32 propagate NaNs, the compare and select matches SSE and the scalar code
+    pub(super) fn min(self, other: Self) -> Self {
+        Self(neon!(vbslq_f32(
+            vcltq_f32(self.0, other.0),
+            self.0,
+            other.0
+        )))
+    }
+
+    pub(super) fn max(self, other: Self) -> Self {
+        Self(neon!(vbslq_f32(
+            vcgtq_f32(self.0, other.0),
+            self.0,
+            other.0
+        )))
+    }
+
+    pub(super) fn cmp_eq(self, other: Self) -> Mask32x4 {
+        Mask32x4(neon!(vceqq_f32(self.0, other.0)))
+    }
+
+    pub(super) fn cmp_ne(self, other: Self) -> Mask32x4 {
+        Mask32x4(neon!(vmvnq_u32(vceqq_f32(self.0, other.0))))
+    }
+
+    pub(super) fn cmp_lt(self, other: Self) -> Mask32x4 {
+        Mask32x4(neon!(vcltq_f32(self.0, other.0)))
+    }
+
+    pub(super) fn cmp_le(self, other: Self) -> Mask32x4 {
+        Mask32x4(neon!(vcleq_f32(self.0, other.0)))
+    }
+
+    pub(super) fn cmp_gt(self, other: Self) -> Mask32x4 {
+        Mask32x4(neon!(vcgtq_f32(self.0, other.0)))
+    }
+
+    pub(super) fn cmp_ge(self, other: Self) -> Mask32x4 {
+        Mask32x4(neon!(vcgeq_f32(self.0, other.0)))
+    }
+
+    pub(super) fn select(mask: Mask32x4, a: Self, b: Self) -> Self {
+        Self(neon!(vbslq_f32(mask.0, a.0, b.0)))
+    }
+
+    pub(super) fn shuffle_aebf(self, other: Self) -> Self {
+        Self(neon!(vzip1q_f32(self.0, other.0)))
+    }
+
+    pub(super) fn shuffle_cgdh(self, other: Self) -> Self {
+        Self(neon!(vzip2q_f32(self.0, other.0)))
+    }
+
+    pub(super) fn shuffle_abef(self, other: Self) -> Self {
+        Self(neon!(vcombine_f32(
+            vget_low_f32(self.0),
+            vget_low_f32(other.0)
+        )))
+    }
+
+    pub(super) fn shuffle_ghcd(self, other: Self) -> Self {
+        Self(neon!(vcombine_f32(
+            vget_high_f32(other.0),
+            vget_high_f32(self.0)
+        )))
+    }
+}
+
+impl Mask32x4 {
+    pub(super) fn from_array(values: [bool; 4]) -> Self {
+        let values = values.map(|v| if v { u32::MAX } else { 0 });
+        // Safety: values points to four integers
+        Self(unsafe { vld1q_u32(values.as_ptr()) })
+    }
+
+    pub(super) fn to_array(self) -> [bool; 4] {
+        let mut values = [0; 4];
+        // Safety: values has room for four integers
+        unsafe { vst1q_u32(values.as_mut_ptr(), self.0) };
+        values.map(|v| v != 0)
+    }
+
+    pub(super) fn and(self, other: Self) -> Self {
+        Self(neon!(vandq_u32(self.0, other.0)))
+    }
+
+    pub(super) fn or(self, other: Self) -> Self {
+        Self(neon!(vorrq_u32(self.0, other.0)))
+    }
+
+    pub(super) fn xor(self, other: Self) -> Self {
+        Self(neon!(veorq_u32(self.0, other.0)))
+    }
+
+    pub(super) fn not(self) -> Self {
+        Self(neon!(vmvnq_u32(self.0)))
+    }
+}
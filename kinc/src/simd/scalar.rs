@@ -0,0 +1,159 @@
+#[derive(Copy, Clone)]
+pub(super) struct F32x4([f32; 4]);
+
+#[derive(Copy, Clone)]
+pub(super) struct Mask32x4([bool; 4]);
+
+impl F32x4 {
+    fn map(self, f: impl Fn(f32) -> f32) -> Self {
+        Self(self.0.map(f))
+    }
+
+    fn zip(self, other: Self, f: impl Fn(f32, f32) -> f32) -> Self {
+        let (a, b) = (self.0, other.0);
+        Self([f(a[0], b[0]), f(a[1], b[1]), f(a[2], b[2]), f(a[3], b[3])])
+    }
+
+    fn cmp(self, other: Self, f: impl Fn(f32, f32) -> bool) -> Mask32x4 {
+        let (a, b) = (self.0, other.0);
+        Mask32x4([f(a[0], b[0]), f(a[1], b[1]), f(a[2], b[2]), f(a[3], b[3])])
+    }
+
+    pub(super) fn splat(value: f32) -> Self {
+        Self([value; 4])
+    }
+
+    pub(super) fn load(values: &[f32; 4]) -> Self {
+        Self(*values)
+    }
+
+    pub(super) fn store(self) -> [f32; 4] {
+        self.0
+    }
+
+    pub(super) fn add(self, other: Self) -> Self {
+        self.zip(other, |a, b| a + b)
+    }
+
+    pub(super) fn sub(self, other: Self) -> Self {
+        self.zip(other, |a, b| a - b)
+    }
+
+    pub(super) fn mul(self, other: Self) -> Self {
+        self.zip(other, |a, b| a * b)
+    }
+
+    pub(super) fn div(self, other: Self) -> Self {
+        self.zip(other, |a, b| a / b)
+    }
+
+    pub(super) fn neg(self) -> Self {
+        self.map(|a| -a)
+    }
+
+    pub(super) fn abs(self) -> Self {
+        self.map(|a| f32::from_bits(a.to_bits() & 0x7fff_ffff))
+    }
+
+    pub(super) fn sqrt(self) -> Self {
+        // Safety: plain math function without side effects
+        self.map(|a| unsafe { crate::sys::kinc_sqrt(a) })
+    }
+
+    pub(super) fn recip_approx(self) -> Self {
+        self.map(|a| 1.0 / a)
+    }
+
+    pub(super) fn rsqrt_approx(self) -> Self {
+        self.sqrt().recip_approx()
+    }
+
+    pub(super) fn min(self, other: Self) -> Self {
+        self.zip(other, |a, b| if a < b { a } else { b })
+    }
+
+    pub(super) fn max(self, other: Self) -> Self {
+        self.zip(other, |a, b| if a > b { a } else { b })
+    }
+
+    pub(super) fn cmp_eq(self, other: Self) -> Mask32x4 {
+        self.cmp(other, |a, b| a == b)
+    }
+
+    pub(super) fn cmp_ne(self, other: Self) -> Mask32x4 {
+        self.cmp(other, |a, b| a != b)
+    }
+
+    pub(super) fn cmp_lt(self, other: Self) -> Mask32x4 {
+        self.cmp(other, |a, b| a < b)
+    }
+
+    pub(super) fn cmp_le(self, other: Self) -> Mask32x4 {
+        self.cmp(other, |a, b| a <= b)
+    }
+
+    pub(super) fn cmp_gt(self, other: Self) -> Mask32x4 {
+        self.cmp(other, |a, b| a > b)
+    }
+
+    pub(super) fn cmp_ge(self, other: Self) -> Mask32x4 {
+        self.cmp(other, |a, b| a >= b)
+    }
+
+    pub(super) fn select(mask: Mask32x4, a: Self, b: Self) -> Self {
+        let m = mask.0;
+        let (a, b) = (a.0, b.0);
+        Self([0, 1, 2, 3].map(|i| if m[i] { a[i] } else { b[i] }))
+    }
+
+    pub(super) fn shuffle_aebf(self, other: Self) -> Self {
+        let (a, b) = (self.0, other.0);
+        Self([a[0], b[0], a[1], b[1]])
+    }
+
+    pub(super) fn shuffle_cgdh(self, other: Self) -> Self {
+        let (a, b) = (self.0, other.0);
+        Self([a[2], b[2], a[3], b[3]])
+    }
+
+    pub(super) fn shuffle_abef(self, other: Self) -> Self {
+        let (a, b) = (self.0, other.0);
+        Self([a[0], a[1], b[0], b[1]])
+    }
+
+    pub(super) fn shuffle_ghcd(self, other: Self) -> Self {
+        let (a, b) = (self.0, other.0);
+        Self([b[2], b[3], a[2], a[3]])
+    }
+}
+
+impl Mask32x4 {
+    fn zip(self, other: Self, f: impl Fn(bool, bool) -> bool) -> Self {
+        let (a, b) = (self.0, other.0);
+        Self([f(a[0], b[0]), f(a[1], b[1]), f(a[2], b[2]), f(a[3], b[3])])
+    }
+
+    pub(super) fn from_array(values: [bool; 4]) -> Self {
+        Self(values)
+    }
+
+    pub(super) fn to_array(self) -> [bool; 4] {
+        self.0
+    }
+
+    pub(super) fn and(self, other: Self) -> Self {
+        self.zip(other, |a, b| a & b)
+    }
+
+    pub(super) fn or(self, other: Self) -> Self {
+        self.zip(other, |a, b| a | b)
+    }
+
+    pub(super) fn xor(self, other: Self) -> Self {
+        self.zip(other, |a, b| a ^ b)
+    }
+
+    pub(super) fn not(self) -> Self {
+        Self(self.0.map(|a| !a))
+    }
+}
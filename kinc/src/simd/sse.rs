@@ -0,0 +1,163 @@
+#[cfg(target_arch = "x86")]
+use core::arch::x86::*;
+#[cfg(target_arch = "x86_64")]
+use core::arch::x86_64::*;
+
+/// Calls an SSE intrinsic, this module is only compiled when SSE is enabled for the target.
+macro_rules! sse {
+    ($e:expr) => {
+        // Safety: SSE is enabled at compile time
+        unsafe { $e }
+    };
+}
+
+#[derive(Copy, Clone)]
+pub(super) struct F32x4(__m128);
+
+#[derive(Copy, Clone)]
+pub(super) struct Mask32x4(__m128);
+
+fn sign_bits() -> __m128 {
+    sse!(_mm_set1_ps(-0.0))
+}
+
+fn all_bits() -> __m128 {
+    sse!(_mm_set1_ps(f32::from_bits(u32::MAX)))
+}
+
+impl F32x4 {
+    pub(super) fn splat(value: f32) -> Self {
+        Self(sse!(_mm_set1_ps(value)))
+    }
+
+    pub(super) fn load(values: &[f32; 4]) -> Self {
+        // Safety: values points to four floats, loadu has no alignment requirement
+        Self(unsafe { _mm_loadu_ps(values.as_ptr()) })
+    }
+
+    pub(super) fn store(self) -> [f32; 4] {
+        let mut values = [0.0; 4];
+        // Safety: values has room for four floats, storeu has no alignment requirement
+        unsafe { _mm_storeu_ps(values.as_mut_ptr(), self.0) };
+        values
+    }
+
+    pub(super) fn add(self, other: Self) -> Self {
+        Self(sse!(_mm_add_ps(self.0, other.0)))
+    }
+
+    pub(super) fn sub(self, other: Self) -> Self {
+        Self(sse!(_mm_sub_ps(self.0, other.0)))
+    }
+
+    pub(super) fn mul(self, other: Self) -> Self {
+        Self(sse!(_mm_mul_ps(self.0, other.0)))
+    }
+
+    pub(super) fn div(self, other: Self) -> Self {
+        Self(sse!(_mm_div_ps(self.0, other.0)))
+    }
+
+    pub(super) fn neg(self) -> Self {
+        Self(sse!(_mm_xor_ps(self.0, sign_bits())))
+    }
+
+    pub(super) fn abs(self) -> Self {
+        Self(sse!(_mm_andnot_ps(sign_bits(), self.0)))
+    }
+
+    pub(super) fn sqrt(self) -> Self {
+        Self(sse!(_mm_sqrt_ps(self.0)))
+    }
+
+    pub(super) fn recip_approx(self) -> Self {
+        Self(sse!(_mm_rcp_ps(self.0)))
+    }
+
+    pub(super) fn rsqrt_approx(self) -> Self {
+        Self(sse!(_mm_rsqrt_ps(self.0)))
+    }
+
+    pub(super) fn min(self, other: Self) -> Self {
+        Self(sse!(_mm_min_ps(self.0, other.0)))
+    }
+
+    pub(super) fn max(self, other: Self) -> Self {
+        Self(sse!(_mm_max_ps(self.0, other.0)))
+    }
+
+    pub(super) fn cmp_eq(self, other: Self) -> Mask32x4 {
+        Mask32x4(sse!(_mm_cmpeq_ps(self.0, other.0)))
+    }
+
+    pub(super) fn cmp_ne(self, other: Self) -> Mask32x4 {
+        Mask32x4(sse!(_mm_cmpneq_ps(self.0, other.0)))
+    }
+
+    pub(super) fn cmp_lt(self, other: Self) -> Mask32x4 {
+        Mask32x4(sse!(_mm_cmplt_ps(self.0, other.0)))
+    }
+
+    pub(super) fn cmp_le(self, other: Self) -> Mask32x4 {
+        Mask32x4(sse!(_mm_cmple_ps(self.0, other.0)))
+    }
+
+    pub(super) fn cmp_gt(self, other: Self) -> Mask32x4 {
+        Mask32x4(sse!(_mm_cmpgt_ps(self.0, other.0)))
+    }
+
+    pub(super) fn cmp_ge(self, other: Self) -> Mask32x4 {
+        Mask32x4(sse!(_mm_cmpge_ps(self.0, other.0)))
+    }
+
+    pub(super) fn select(mask: Mask32x4, a: Self, b: Self) -> Self {
+        Self(sse!(_mm_or_ps(
+            _mm_and_ps(mask.0, a.0),
+            _mm_andnot_ps(mask.0, b.0)
+        )))
+    }
+
+    pub(super) fn shuffle_aebf(self, other: Self) -> Self {
+        Self(sse!(_mm_unpacklo_ps(self.0, other.0)))
+    }
+
+    pub(super) fn shuffle_cgdh(self, other: Self) -> Self {
+        Self(sse!(_mm_unpackhi_ps(self.0, other.0)))
+    }
+
+    pub(super) fn shuffle_abef(self, other: Self) -> Self {
+        Self(sse!(_mm_movelh_ps(self.0, other.0)))
+    }
+
+    pub(super) fn shuffle_ghcd(self, other: Self) -> Self {
+        Self(sse!(_mm_movehl_ps(self.0, other.0)))
+    }
+}
+
+impl Mask32x4 {
+    pub(super) fn from_array(values: [bool; 4]) -> Self {
+        let [a, b, c, d] = values.map(|v| f32::from_bits(if v { u32::MAX } else { 0 }));
+        Self(sse!(_mm_setr_ps(a, b, c, d)))
+    }
+
+    pub(super) fn to_array(self) -> [bool; 4] {
+        let bits = sse!(_mm_movemask_ps(self.0));
+        [0, 1, 2, 3].map(|i| bits & (1 << i) != 0)
+    }
+
+    pub(super) fn and(self, other: Self) -> Self {
+        Self(sse!(_mm_and_ps(self.0, other.0)))
+    }
+
+    pub(super) fn or(self, other: Self) -> Self {
+        Self(sse!(_mm_or_ps(self.0, other.0)))
+    }
+
+    pub(super) fn xor(self, other: Self) -> Self {
+        Self(sse!(_mm_xor_ps(self.0, other.0)))
+    }
+
+    pub(super) fn not(self) -> Self {
+        Self(sse!(_mm_xor_ps(self.0, all_bits())))
+    }
+}
@@ -0,0 +1,111 @@
+use core::{
+    ffi::{c_char, CStr},
+    time::Duration,
+};
+
+use crate::sys::*;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Platform {
+    Windows,
+    WindowsApp,
+    Linux,
+    MacOS,
+    IOS,
+    TVOS,
+    Android,
+    Html5,
+    /// A platform the bindings don't know by name, see [`platform_name`].
+    Unknown,
+}
+
+impl Platform {
+    pub fn current() -> Self {
+        match platform_name() {
+            "Windows" => Platform::Windows,
+            "WindowsApp" => Platform::WindowsApp,
+            "Linux" => Platform::Linux,
+            "macOS" => Platform::MacOS,
+            "iOS" => Platform::IOS,
+            "tvOS" => Platform::TVOS,
+            "Android" => Platform::Android,
+            "HTML5" => Platform::Html5,
+            _ => Platform::Unknown,
+        }
+    }
+}
+
+/// # Safety
+/// `s` has to be null or point to a C string that lives for the rest of the program.
+unsafe fn static_str(s: *const c_char) -> &'static str {
+    if s.is_null() {
+        ""
+    } else {
+        // Safety: s is a valid, static C string
+        unsafe { CStr::from_ptr(s) }.to_str().unwrap_or("")
+    }
+}
+
+/// The name Kinc uses for the current platform, like `"Windows"` or `"Android"`.
+pub fn platform_name() -> &'static str {
+    // Safety: Kinc returns a string literal
+    unsafe { static_str(kinc_system_id()) }
+}
+
+/// The language the user has set up, usually as a two letter code like `"en"`.
+pub fn language() -> &'static str {
+    // Safety: Kinc returns a string literal or a static buffer which is filled once
+    unsafe { static_str(kinc_language()) }
+}
+
+/// The time since the application started, with the highest resolution the platform offers.
+pub fn time() -> Duration {
+    // Safety: plain query without side effects
+    Duration::try_from_secs_f64(unsafe { kinc_time() }).unwrap_or_default()
+}
+
+/// The number of [`timestamp`] ticks per second.
+pub fn frequency() -> f64 {
+    // Safety: plain query without side effects
+    unsafe { kinc_frequency() }
+}
+
+/// The raw value of the high-resolution timer, see [`frequency`].
+pub fn timestamp() -> u64 {
+    // Safety: plain query without side effects
+    unsafe { kinc_timestamp() }
+}
+
+/// The refresh rate of the primary display in Hz.
+pub fn refresh_rate() -> i32 {
+    // Safety: Kinc initializes the displays in kinc_init
+    unsafe { kinc_display_current_mode(kinc_primary_display()).frequency }
+}
+
+/// Vibrates the device, on platforms that support it.
+pub fn vibrate(duration: Duration) {
+    let milliseconds = duration.as_millis().try_into().unwrap_or(i32::MAX);
+    // Safety: does nothing on platforms without vibration
+    unsafe { kinc_vibrate(milliseconds) }
+}
+
+/// Starts logging in to the platform's user account system, see [`waiting_for_login`].
+pub fn login() {
+    // Safety: does nothing on platforms without a login
+    unsafe { kinc_login() }
+}
+
+pub fn waiting_for_login() -> bool {
+    // Safety: plain query without side effects
+    unsafe { kinc_waiting_for_login() }
+}
+
+pub fn unlock_achievement(id: i32) {
+    // Safety: does nothing on platforms without achievements
+    unsafe { kinc_unlock_achievement(id) }
+}
+
+pub fn copy_to_clipboard(text: &CStr) {
+    // Safety: text is a valid C string, which Kinc copies
+    unsafe { kinc_copy_to_clipboard(text.as_ptr()) }
+}
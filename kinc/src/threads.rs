@@ -0,0 +1,556 @@
+use alloc::{boxed::Box, sync::Arc};
+use core::{
+    cell::UnsafeCell,
+    ffi::{c_void, CStr},
+    marker::PhantomData,
+    mem::MaybeUninit,
+    ops::{Deref, DerefMut},
+    ptr::NonNull,
+    time::Duration,
+};
+
+use crate::sys::*;
+use atomic::{AtomicBool, Ordering};
+
+/// Kinc's atomics are thin macros around the compiler intrinsics, which is exactly what `core`'s atomics are.
+pub mod atomic {
+    pub use core::sync::atomic::*;
+
+    /// An `f32` that can be shared between threads, the counterpart of `KINC_ATOMIC_EXCHANGE_FLOAT`.
+    #[derive(Debug, Default)]
+    #[repr(transparent)]
+    pub struct AtomicF32(AtomicU32);
+
+    impl AtomicF32 {
+        pub const fn new(value: f32) -> Self {
+            Self(AtomicU32::new(value.to_bits()))
+        }
+
+        pub fn load(&self, order: Ordering) -> f32 {
+            f32::from_bits(self.0.load(order))
+        }
+
+        pub fn store(&self, value: f32, order: Ordering) {
+            self.0.store(value.to_bits(), order)
+        }
+
+        pub fn swap(&self, value: f32, order: Ordering) -> f32 {
+            f32::from_bits(self.0.swap(value.to_bits(), order))
+        }
+    }
+}
+
+pub fn sleep(duration: Duration) {
+    // Safety: kinc_thread_sleep has no preconditions
+    unsafe { kinc_thread_sleep(duration.as_millis().try_into().unwrap_or(i32::MAX)) }
+}
+
+/// Sets the name of the calling thread, as shown in debuggers.
+pub fn set_name(name: &CStr) {
+    // Safety: name is a valid C string
+    unsafe { kinc_thread_set_name(name.as_ptr()) }
+}
+
+struct Packet<'a, T> {
+    main: Option<Box<dyn FnOnce() -> T + Send + 'a>>,
+    result: Option<T>,
+    scope: Option<Arc<ScopeData>>,
+}
+
+extern "C" fn _thread_main<T>(param: *mut c_void) {
+    // Safety: param is the packet owned by the `Thread` that started this thread,
+    // which is not touched by anyone else until the thread has been joined.
+    let packet = unsafe { &mut *param.cast::<Packet<'static, T>>() };
+    let main = packet.main.take().unwrap();
+    packet.result = Some(main());
+    if let Some(scope) = packet.scope.take() {
+        scope.finish_thread();
+    }
+}
+
+/// A native thread, which is joined when dropped.
+pub struct Thread<'a, T> {
+    thread: NonNull<kinc_thread_t>,
+    packet: NonNull<Packet<'a, T>>,
+}
+
+impl<T: Send + 'static> Thread<'static, T> {
+    pub fn spawn<F>(f: F) -> Self
+    where
+        F: FnOnce() -> T + Send + 'static,
+    {
+        // Safety: the closure and its result are 'static
+        unsafe { Self::spawn_unchecked(Box::new(f), None) }
+    }
+}
+
+impl<'a, T> Thread<'a, T> {
+    /// # Safety
+    /// Everything `main` borrows has to outlive the thread,
+    /// which is either guaranteed by a `'static` bound or by `scope`.
+    unsafe fn spawn_unchecked(
+        main: Box<dyn FnOnce() -> T + Send + 'a>,
+        scope: Option<Arc<ScopeData>>,
+    ) -> Self {
+        let packet = NonNull::from(Box::leak(Box::new(Packet {
+            main: Some(main),
+            result: None,
+            scope,
+        })));
+        // Kinc passes the pointer to the thread object to the new thread, so it must not move.
+        let thread = NonNull::from(Box::leak(Box::new(MaybeUninit::<kinc_thread_t>::zeroed())));
+        let thread = thread.cast::<kinc_thread_t>();
+        // Safety: thread points to a pinned thread object, and packet stays valid until the thread is joined
+        unsafe {
+            kinc_thread_init(
+                thread.as_ptr(),
+                Some(_thread_main::<T>),
+                packet.as_ptr().cast(),
+            )
+        };
+        Self { thread, packet }
+    }
+
+    /// Waits for the thread to finish and returns its result.
+    pub fn join(self) -> T {
+        let this = core::mem::ManuallyDrop::new(self);
+        // Safety: the thread is joined exactly once, `this` isn't dropped
+        unsafe { this.wait_and_take() }.unwrap()
+    }
+
+    /// # Safety
+    /// May only be called once.
+    unsafe fn wait_and_take(&self) -> Option<T> {
+        // Safety: the thread was initialized in spawn_unchecked and hasn't been joined yet
+        unsafe {
+            kinc_thread_wait_and_destroy(self.thread.as_ptr());
+            drop(Box::from_raw(self.thread.as_ptr()));
+            Box::from_raw(self.packet.as_ptr()).result
+        }
+    }
+}
+
+impl<T> Drop for Thread<'_, T> {
+    fn drop(&mut self) {
+        // Safety: join forgets self, so the thread hasn't been joined yet
+        unsafe {
+            self.wait_and_take();
+        }
+    }
+}
+
+// Safety: the thread handle only gives access to the result, once the thread has finished
+unsafe impl<T: Send> Send for Thread<'_, T> {}
+unsafe impl<T: Send> Sync for Thread<'_, T> {}
+
+struct ScopeData {
+    running: Mutex<usize>,
+    finished: Event,
+}
+
+impl ScopeData {
+    fn finish_thread(&self) {
+        let mut running = self.running.lock();
+        *running -= 1;
+        if *running == 0 {
+            self.finished.signal();
+        }
+    }
+
+    fn wait_for_threads(&self) {
+        while *self.running.lock() != 0 {
+            self.finished.wait();
+        }
+    }
+}
+
+/// Waits for the scope's threads when dropped, also while a panic unwinds out of the scope.
+struct WaitForThreads<'a>(&'a ScopeData);
+
+impl Drop for WaitForThreads<'_> {
+    fn drop(&mut self) {
+        self.0.wait_for_threads();
+    }
+}
+
+pub struct Scope<'scope, 'env: 'scope> {
+    data: Arc<ScopeData>,
+    _scope: PhantomData<&'scope mut &'scope ()>,
+    _env: PhantomData<&'env mut &'env ()>,
+}
+
+impl<'scope> Scope<'scope, '_> {
+    /// Spawns a thread that may borrow from outside the scope.
+    /// All threads spawned in the scope are joined before [`scope`] returns.
+    pub fn spawn<F, T>(&'scope self, f: F) -> Thread<'scope, T>
+    where
+        F: FnOnce() -> T + Send + 'scope,
+        T: Send + 'scope,
+    {
+        *self.data.running.lock() += 1;
+        // Safety: `scope` waits for the thread to finish running `f` before returning
+        unsafe { Thread::spawn_unchecked(Box::new(f), Some(self.data.clone())) }
+    }
+}
+
+/// Creates a scope for spawning threads that borrow local variables, like `std::thread::scope`.
+pub fn scope<'env, F, T>(f: F) -> T
+where
+    F: for<'scope> FnOnce(&'scope Scope<'scope, 'env>) -> T,
+{
+    let scope = Scope {
+        data: Arc::new(ScopeData {
+            running: Mutex::new(0),
+            finished: Event::new(true),
+        }),
+        _scope: PhantomData,
+        _env: PhantomData,
+    };
+    // Without std there is no catch_unwind, the guard waits for threads whose handles were forgotten
+    // before a panic in `f` can leave the scope.
+    let _wait = WaitForThreads(&scope.data);
+    f(&scope)
+}
+
+/// A mutex on top of Kinc's, which are recursive.
+///
+/// Locking a mutex again on the thread that already holds it panics
+/// (and [`Mutex::try_lock`] returns `None`), since that would hand out a second `&mut T`.
+pub struct Mutex<T: ?Sized> {
+    mutex: Box<UnsafeCell<kinc_mutex_t>>,
+    /// Whether a guard exists. Only the thread holding the Kinc mutex can see it set,
+    /// so finding it set right after locking means the lock was recursive.
+    locked: AtomicBool,
+    data: UnsafeCell<T>,
+}
+
+// Safety: the mutex makes sure that only one thread accesses the data at a time
+unsafe impl<T: ?Sized + Send> Send for Mutex<T> {}
+unsafe impl<T: ?Sized + Send> Sync for Mutex<T> {}
+
+impl<T> Mutex<T> {
+    pub fn new(value: T) -> Self {
+        // The mutex is initialized in place, pthread mutexes must not move once initialized.
+        // Safety: the zeroed mutex is only used after kinc_mutex_init
+        let mutex: Box<UnsafeCell<kinc_mutex_t>> =
+            Box::new(UnsafeCell::new(unsafe { core::mem::zeroed() }));
+        // Safety: the mutex is boxed and never moves again
+        unsafe { kinc_mutex_init(mutex.get()) };
+        Self {
+            mutex,
+            locked: AtomicBool::new(false),
+            data: UnsafeCell::new(value),
+        }
+    }
+
+    pub fn into_inner(self) -> T {
+        let this = core::mem::ManuallyDrop::new(self);
+        // Safety: `this` is never used or dropped again after moving out its fields
+        unsafe {
+            let mutex = core::ptr::read(&this.mutex);
+            kinc_mutex_destroy(mutex.get());
+            core::ptr::read(&this.data).into_inner()
+        }
+    }
+}
+
+impl<T: ?Sized> Mutex<T> {
+    /// # Panics
+    /// If the current thread already holds the lock.
+    pub fn lock(&self) -> MutexGuard<'_, T> {
+        // Safety: the mutex is initialized
+        unsafe { kinc_mutex_lock(self.mutex.get()) };
+        self.guard()
+            .expect("the mutex is already locked by the current thread")
+    }
+
+    /// Returns `None` if the mutex is locked, including by the current thread.
+    pub fn try_lock(&self) -> Option<MutexGuard<'_, T>> {
+        // Safety: the mutex is initialized
+        if unsafe { kinc_mutex_try_to_lock(self.mutex.get()) } {
+            self.guard()
+        } else {
+            None
+        }
+    }
+
+    /// Creates the guard after locking the Kinc mutex, unless this thread already had a guard.
+    fn guard(&self) -> Option<MutexGuard<'_, T>> {
+        if self.locked.swap(true, Ordering::Relaxed) {
+            // Safety: the recursive lock above has to be undone, the outer guard still holds the mutex
+            unsafe { kinc_mutex_unlock(self.mutex.get()) };
+            None
+        } else {
+            Some(MutexGuard {
+                mutex: self,
+                _not_send: PhantomData,
+            })
+        }
+    }
+
+    pub fn get_mut(&mut self) -> &mut T {
+        self.data.get_mut()
+    }
+}
+
+impl<T: Default> Default for Mutex<T> {
+    fn default() -> Self {
+        Self::new(Default::default())
+    }
+}
+
+impl<T: ?Sized> Drop for Mutex<T> {
+    fn drop(&mut self) {
+        // Safety: the mutex is initialized and not locked, since no guard can outlive it
+        unsafe { kinc_mutex_destroy(self.mutex.get()) }
+    }
+}
+
+pub struct MutexGuard<'a, T: ?Sized> {
+    mutex: &'a Mutex<T>,
+    /// Kinc's mutexes have to be unlocked on the thread that locked them.
+    _not_send: PhantomData<*const ()>,
+}
+
+// Safety: the guard only hands out references to the data
+unsafe impl<T: ?Sized + Sync> Sync for MutexGuard<'_, T> {}
+
+impl<T: ?Sized> Deref for MutexGuard<'_, T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        // Safety: the mutex is locked
+        unsafe { &*self.mutex.data.get() }
+    }
+}
+
+impl<T: ?Sized> DerefMut for MutexGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        // Safety: the mutex is locked
+        unsafe { &mut *self.mutex.data.get() }
+    }
+}
+
+impl<T: ?Sized> Drop for MutexGuard<'_, T> {
+    fn drop(&mut self) {
+        self.mutex.locked.store(false, Ordering::Relaxed);
+        // Safety: the mutex was locked by this guard, on this thread
+        unsafe { kinc_mutex_unlock(self.mutex.mutex.get()) }
+    }
+}
+
+pub struct Event {
+    event: Box<UnsafeCell<kinc_event_t>>,
+}
+
+// Safety: Kinc's events are thread-safe
+unsafe impl Send for Event {}
+unsafe impl Sync for Event {}
+
+impl Event {
+    /// If `auto_clear` is set, the event is reset again whenever a `wait` returns.
+    pub fn new(auto_clear: bool) -> Self {
+        // Safety: the zeroed event is only used after kinc_event_init
+        let event: Box<UnsafeCell<kinc_event_t>> =
+            Box::new(UnsafeCell::new(unsafe { core::mem::zeroed() }));
+        // Safety: the event is boxed for the same reason as Mutex::mutex
+        unsafe { kinc_event_init(event.get(), auto_clear) };
+        Self { event }
+    }
+
+    pub fn signal(&self) {
+        // Safety: the event is initialized
+        unsafe { kinc_event_signal(self.event.get()) }
+    }
+
+    pub fn wait(&self) {
+        // Safety: the event is initialized
+        unsafe { kinc_event_wait(self.event.get()) }
+    }
+
+    /// Returns `false` if the event wasn't signaled within `timeout`.
+    pub fn wait_timeout(&self, timeout: Duration) -> bool {
+        // Safety: the event is initialized
+        unsafe { kinc_event_try_to_wait(self.event.get(), timeout.as_secs_f64()) }
+    }
+
+    pub fn reset(&self) {
+        // Safety: the event is initialized
+        unsafe { kinc_event_reset(self.event.get()) }
+    }
+}
+
+impl Drop for Event {
+    fn drop(&mut self) {
+        // Safety: the event is initialized
+        unsafe { kinc_event_destroy(self.event.get()) }
+    }
+}
+
+pub struct Semaphore {
+    semaphore: Box<UnsafeCell<kinc_semaphore_t>>,
+}
+
+// Safety: Kinc's semaphores are thread-safe
+unsafe impl Send for Semaphore {}
+unsafe impl Sync for Semaphore {}
+
+impl Semaphore {
+    pub fn new(current: i32, max: i32) -> Self {
+        // Safety: the zeroed semaphore is only used after kinc_semaphore_init
+        let semaphore: Box<UnsafeCell<kinc_semaphore_t>> =
+            Box::new(UnsafeCell::new(unsafe { core::mem::zeroed() }));
+        // Safety: the semaphore is boxed and never moves again
+        unsafe { kinc_semaphore_init(semaphore.get(), current, max) };
+        Self { semaphore }
+    }
+
+    pub fn release(&self, count: i32) {
+        // Safety: the semaphore is initialized
+        unsafe { kinc_semaphore_release(self.semaphore.get(), count) }
+    }
+
+    pub fn acquire(&self) {
+        // Safety: the semaphore is initialized
+        unsafe { kinc_semaphore_acquire(self.semaphore.get()) }
+    }
+
+    /// Returns `false` if the semaphore couldn't be acquired within `timeout`.
+    pub fn acquire_timeout(&self, timeout: Duration) -> bool {
+        // Safety: the semaphore is initialized
+        unsafe { kinc_semaphore_try_to_acquire(self.semaphore.get(), timeout.as_secs_f64()) }
+    }
+}
+
+impl Drop for Semaphore {
+    fn drop(&mut self) {
+        // Safety: the semaphore is initialized
+        unsafe { kinc_semaphore_destroy(self.semaphore.get()) }
+    }
+}
+
+/// A per-thread value, initialized lazily on first access.
+///
+/// Kinc's thread locals have no destructors, so values of threads other than
+/// the one dropping the `ThreadLocal` are leaked.
+pub struct ThreadLocal<T> {
+    local: Box<UnsafeCell<kinc_thread_local_t>>,
+    _phantom: PhantomData<T>,
+}
+
+// Safety: every thread only ever accesses its own value
+unsafe impl<T: Send> Send for ThreadLocal<T> {}
+unsafe impl<T: Send> Sync for ThreadLocal<T> {}
+
+impl<T> ThreadLocal<T> {
+    pub fn new() -> Self {
+        // Safety: the zeroed thread local is only used after kinc_thread_local_init
+        let local: Box<UnsafeCell<kinc_thread_local_t>> =
+            Box::new(UnsafeCell::new(unsafe { core::mem::zeroed() }));
+        // Safety: local is a valid pointer
+        unsafe { kinc_thread_local_init(local.get()) };
+        Self {
+            local,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Returns the value of the current thread, if it has been initialized.
+    pub fn get(&self) -> Option<&T> {
+        // Safety: the thread local is initialized, and only ever holds null or a leaked Box<T>
+        unsafe { kinc_thread_local_get(self.local.get()).cast::<T>().as_ref() }
+    }
+
+    pub fn get_or(&self, init: impl FnOnce() -> T) -> &T {
+        if let Some(value) = self.get() {
+            return value;
+        }
+        let value = Box::into_raw(Box::new(init()));
+        // Safety: the thread local is initialized, value is valid until the thread local is dropped
+        unsafe {
+            kinc_thread_local_set(self.local.get(), value.cast());
+            &*value
+        }
+    }
+}
+
+impl<T> Default for ThreadLocal<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Drop for ThreadLocal<T> {
+    fn drop(&mut self) {
+        // Safety: the value of the current thread is a leaked Box<T> that's not borrowed anymore
+        unsafe {
+            let value = kinc_thread_local_get(self.local.get()).cast::<T>();
+            if !value.is_null() {
+                drop(Box::from_raw(value));
+            }
+            kinc_thread_local_destroy(self.local.get());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::panic::AssertUnwindSafe;
+
+    #[test]
+    fn relocking_on_the_same_thread_fails() {
+        let mutex = Mutex::new(0);
+        let guard = mutex.lock();
+        assert!(mutex.try_lock().is_none());
+        drop(guard);
+        assert!(mutex.try_lock().is_some());
+    }
+
+    #[test]
+    #[should_panic(expected = "already locked")]
+    fn recursive_lock_panics() {
+        let mutex = Mutex::new(0);
+        let _guard = mutex.lock();
+        let _second = mutex.lock();
+    }
+
+    #[test]
+    fn guards_exclude_other_threads() {
+        let mutex = Mutex::new(0);
+        scope(|scope| {
+            // Dropping a handle joins its thread, so all of them are spawned before any is joined
+            let threads: Vec<_> = (0..4)
+                .map(|_| {
+                    scope.spawn(|| {
+                        for _ in 0..1000 {
+                            let mut guard = mutex.lock();
+                            let value = *guard;
+                            sleep(Duration::ZERO);
+                            *guard = value + 1;
+                        }
+                    })
+                })
+                .collect();
+            for thread in threads {
+                thread.join();
+            }
+        });
+        assert_eq!(mutex.into_inner(), 4000);
+    }
+
+    #[test]
+    fn panicking_scopes_wait_for_forgotten_threads() {
+        let done = AtomicBool::new(false);
+        let result = std::panic::catch_unwind(AssertUnwindSafe(|| {
+            scope(|scope| {
+                core::mem::forget(scope.spawn(|| {
+                    sleep(Duration::from_millis(50));
+                    done.store(true, Ordering::SeqCst);
+                }));
+                panic!("scope body failed");
+            })
+        }));
+        assert!(result.is_err());
+        assert!(done.load(Ordering::SeqCst));
+    }
+}
@@ -0,0 +1,192 @@
+use alloc::boxed::Box;
+use core::{cell::UnsafeCell, ffi::CStr, mem::MaybeUninit, time::Duration};
+
+use crate::{g4::Texture, sys::*, GetRaw};
+
+/// The file extensions of the video formats supported on the current platform.
+pub fn formats() -> impl Iterator<Item = &'static CStr> {
+    // Safety: Kinc returns a static, null-terminated array of C strings
+    let mut formats = unsafe { kinc_video_formats() };
+    core::iter::from_fn(move || {
+        // Safety: formats points into the array, which ends with a null pointer
+        unsafe {
+            if formats.is_null() || (*formats).is_null() {
+                return None;
+            }
+            let format = CStr::from_ptr(*formats);
+            formats = formats.add(1);
+            Some(format)
+        }
+    })
+}
+
+fn duration_from_secs(seconds: f64) -> Duration {
+    Duration::try_from_secs_f64(seconds).unwrap_or_default()
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum VideoError {
+    /// The file extension is not in [`formats`].
+    UnsupportedFormat,
+    /// The file could not be opened.
+    NotFound,
+}
+
+impl core::fmt::Display for VideoError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            VideoError::UnsupportedFormat => write!(f, "unsupported video format"),
+            VideoError::NotFound => write!(f, "could not open video file"),
+        }
+    }
+}
+
+impl core::error::Error for VideoError {}
+
+/// Whether Kinc can open `path` as an asset, which is where its video backends look for it.
+fn asset_exists(path: &CStr) -> bool {
+    // Safety: the reader is only closed if it has been opened
+    unsafe {
+        let mut reader = MaybeUninit::<kinc_file_reader_t>::zeroed();
+        let exists = kinc_file_reader_open(
+            reader.as_mut_ptr(),
+            path.as_ptr(),
+            KINC_FILE_TYPE_ASSET as i32,
+        );
+        if exists {
+            kinc_file_reader_close(reader.as_mut_ptr());
+        }
+        exists
+    }
+}
+
+/// A video file, decoded into a texture while it plays.
+///
+/// Kinc has no API for seeking, use [`Video::stop`] to rewind to the start.
+pub struct Video {
+    // Kinc's backends keep pointers to the video object (eg. in decoder callbacks), so it must not move.
+    video: Box<UnsafeCell<kinc_video_t>>,
+}
+
+impl Video {
+    /// Opens a video file, see [`formats`] for the supported formats.
+    ///
+    /// Kinc's video backends don't report errors themselves, so this checks the extension
+    /// and that the file exists before handing it to Kinc.
+    pub fn open(path: &CStr) -> Result<Self, VideoError> {
+        let extension = path
+            .to_bytes()
+            .rsplit(|c| *c == b'.')
+            .next()
+            .filter(|extension| extension.len() < path.to_bytes().len())
+            .ok_or(VideoError::UnsupportedFormat)?;
+        if !formats().any(|format| format.to_bytes().eq_ignore_ascii_case(extension)) {
+            return Err(VideoError::UnsupportedFormat);
+        }
+        if !asset_exists(path) {
+            return Err(VideoError::NotFound);
+        }
+        // Safety: the zeroed video is only used after kinc_video_init
+        let video: Box<UnsafeCell<kinc_video_t>> =
+            Box::new(UnsafeCell::new(unsafe { core::mem::zeroed() }));
+        // Safety: the video is boxed and never moves again, path is a valid C string
+        unsafe { kinc_video_init(video.get(), path.as_ptr()) };
+        Ok(Self { video })
+    }
+
+    pub fn play(&mut self, looping: bool) {
+        // Safety: the video is initialized
+        unsafe { kinc_video_play(self.get_raw(), looping) }
+    }
+
+    pub fn pause(&mut self) {
+        // Safety: the video is initialized
+        unsafe { kinc_video_pause(self.get_raw()) }
+    }
+
+    /// Stops playback and rewinds to the start.
+    pub fn stop(&mut self) {
+        // Safety: the video is initialized
+        unsafe { kinc_video_stop(self.get_raw()) }
+    }
+
+    /// Decodes the frame for the current time, call this once per frame while the video plays.
+    pub fn update(&mut self) {
+        // Safety: the video is initialized
+        unsafe { kinc_video_update(self.get_raw(), kinc_time()) }
+    }
+
+    pub fn width(&self) -> i32 {
+        // Safety: the video is initialized
+        unsafe { kinc_video_width(self.get_raw()) }
+    }
+
+    pub fn height(&self) -> i32 {
+        // Safety: the video is initialized
+        unsafe { kinc_video_height(self.get_raw()) }
+    }
+
+    pub fn duration(&self) -> Duration {
+        // Safety: the video is initialized
+        duration_from_secs(unsafe { kinc_video_duration(self.get_raw()) })
+    }
+
+    pub fn position(&self) -> Duration {
+        // Safety: the video is initialized
+        duration_from_secs(unsafe { kinc_video_position(self.get_raw()) })
+    }
+
+    pub fn is_paused(&self) -> bool {
+        // Safety: the video is initialized
+        unsafe { kinc_video_paused(self.get_raw()) }
+    }
+
+    pub fn finished(&self) -> bool {
+        // Safety: the video is initialized
+        unsafe { kinc_video_finished(self.get_raw()) }
+    }
+
+    /// The texture holding the current frame, or `None` if no frame has been decoded yet.
+    pub fn current_frame(&self) -> Option<&Texture> {
+        // Safety: the video is initialized, and the texture it returns is owned by it
+        unsafe {
+            let texture = kinc_video_current_image(self.get_raw());
+            (!texture.is_null()).then(|| Texture::from_raw(texture))
+        }
+    }
+}
+
+impl GetRaw<kinc_video_t> for Video {
+    fn get_raw(&self) -> *mut kinc_video_t {
+        self.video.get()
+    }
+}
+
+impl Drop for Video {
+    fn drop(&mut self) {
+        // Safety: self.get_raw is a valid pointer to an initialized video
+        unsafe { kinc_video_destroy(self.get_raw()) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::ffi::CString;
+
+    #[test]
+    fn open_checks_format_and_file() {
+        assert_eq!(
+            Video::open(c"video").err(),
+            Some(VideoError::UnsupportedFormat)
+        );
+        assert_eq!(
+            Video::open(c"video.txt").err(),
+            Some(VideoError::UnsupportedFormat)
+        );
+        if let Some(format) = formats().next() {
+            let path = CString::new(format!("missing.{}", format.to_str().unwrap())).unwrap();
+            assert_eq!(Video::open(&path).err(), Some(VideoError::NotFound));
+        }
+    }
+}
@@ -0,0 +1,234 @@
+//! Head-mounted displays through Kinc's VR interface.
+//!
+//! The headset backend is meant to be compiled in with the `vr` feature, which the build script rejects
+//! until one of Kinc's headset backends and its SDK are wired in. Without it, every function here is a no-op
+//! that reports a default pose, so the same code runs on machines without a headset.
+//!
+//! Kinc renders each eye into render targets it owns, [`render_eye`] binds them for the duration of the closure.
+
+use crate::math::{Mat4, Quat, Vec3};
+#[cfg(feature = "vr")]
+use crate::sys::*;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Eye {
+    Left,
+    Right,
+}
+
+impl Into<i32> for Eye {
+    fn into(self) -> i32 {
+        match self {
+            Eye::Left => 0,
+            Eye::Right => 1,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TrackingOrigin {
+    EyeLevel,
+    FloorLevel,
+    Stage,
+}
+
+#[cfg(feature = "vr")]
+impl Into<kinc_tracking_origin_t> for TrackingOrigin {
+    fn into(self) -> kinc_tracking_origin_t {
+        match self {
+            TrackingOrigin::EyeLevel => kinc_tracking_origin_t_KINC_TRACKING_ORIGIN_EYE_LEVEL,
+            TrackingOrigin::FloorLevel => kinc_tracking_origin_t_KINC_TRACKING_ORIGIN_FLOOR_LEVEL,
+            TrackingOrigin::Stage => kinc_tracking_origin_t_KINC_TRACKING_ORIGIN_STAGE,
+        }
+    }
+}
+
+/// The field of view as tangents of the angles between the view direction and each edge.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Fov {
+    pub left: f32,
+    pub right: f32,
+    pub bottom: f32,
+    pub top: f32,
+}
+
+impl Default for Fov {
+    /// 90 degrees horizontally and vertically.
+    fn default() -> Self {
+        Self {
+            left: 1.0,
+            right: 1.0,
+            bottom: 1.0,
+            top: 1.0,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Pose {
+    pub orientation: Quat,
+    pub position: Vec3,
+    pub fov: Fov,
+}
+
+impl Pose {
+    /// The view matrix, which transforms from world space into the space of this pose.
+    pub fn view(&self) -> Mat4 {
+        Mat4::from_quat(self.orientation.conjugate()) * Mat4::translation(-self.position)
+    }
+
+    /// The projection matrix for the field of view, for the active graphics backend.
+    pub fn projection(&self, near: f32, far: f32) -> Mat4 {
+        Mat4::frustum(
+            -self.fov.left * near,
+            self.fov.right * near,
+            -self.fov.bottom * near,
+            self.fov.top * near,
+            near,
+            far,
+        )
+    }
+}
+
+#[cfg(feature = "vr")]
+impl From<kinc_vr_pose_t> for Pose {
+    fn from(pose: kinc_vr_pose_t) -> Self {
+        Self {
+            orientation: pose.orientation.into(),
+            position: pose.position.into(),
+            fov: Fov {
+                left: pose.left,
+                right: pose.right,
+                bottom: pose.bottom,
+                top: pose.top,
+            },
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct PoseState {
+    pub pose: Pose,
+    pub angular_velocity: Vec3,
+    pub linear_velocity: Vec3,
+    pub angular_acceleration: Vec3,
+    pub linear_acceleration: Vec3,
+    pub tracked: bool,
+    pub visible: bool,
+    pub hmd_presenting: bool,
+    pub hmd_mounted: bool,
+    pub display_lost: bool,
+    pub should_quit: bool,
+    pub should_recenter: bool,
+}
+
+#[cfg(feature = "vr")]
+impl From<kinc_vr_pose_state_t> for PoseState {
+    fn from(state: kinc_vr_pose_state_t) -> Self {
+        Self {
+            pose: state.vrPose.into(),
+            angular_velocity: state.angularVelocity.into(),
+            linear_velocity: state.linearVelocity.into(),
+            angular_acceleration: state.angularAcceleration.into(),
+            linear_acceleration: state.linearAcceleration.into(),
+            tracked: state.trackedDevice,
+            visible: state.isVisible,
+            hmd_presenting: state.hmdPresenting,
+            hmd_mounted: state.hmdMounted,
+            display_lost: state.displayLost,
+            should_quit: state.shouldQuit,
+            should_recenter: state.shouldRecenter,
+        }
+    }
+}
+
+/// Whether a headset backend is compiled in.
+pub const fn is_available() -> bool {
+    cfg!(feature = "vr")
+}
+
+/// The head pose for an eye.
+pub fn sensor_state(eye: Eye) -> PoseState {
+    #[cfg(feature = "vr")]
+    {
+        // Safety: Kinc initializes the VR interface when it creates the window
+        unsafe { kinc_vr_interface_get_sensor_state(eye.into()) }
+            .pose
+            .into()
+    }
+    #[cfg(not(feature = "vr"))]
+    {
+        let _ = eye;
+        PoseState::default()
+    }
+}
+
+/// The pose of a tracked controller.
+pub fn controller(index: i32) -> PoseState {
+    #[cfg(feature = "vr")]
+    {
+        // Safety: Kinc initializes the VR interface when it creates the window
+        unsafe { kinc_vr_interface_get_controller(index) }.into()
+    }
+    #[cfg(not(feature = "vr"))]
+    {
+        let _ = index;
+        PoseState::default()
+    }
+}
+
+/// Starts a frame, call this before rendering the eyes.
+pub fn begin() {
+    #[cfg(feature = "vr")]
+    {
+        // Safety: Kinc initializes the VR interface when it creates the window
+        unsafe { kinc_vr_interface_begin() }
+    }
+}
+
+/// Binds the render target of `eye` and calls `f` with its pose.
+pub fn render_eye<R>(eye: Eye, f: impl FnOnce(&PoseState) -> R) -> R {
+    let state = sensor_state(eye);
+    #[cfg(feature = "vr")]
+    {
+        // Safety: Kinc initializes the VR interface when it creates the window
+        unsafe { kinc_vr_interface_begin_render(eye.into()) }
+    }
+    let result = f(&state);
+    #[cfg(feature = "vr")]
+    {
+        // Safety: begin_render has been called for this eye
+        unsafe { kinc_vr_interface_end_render(eye.into()) }
+    }
+    result
+}
+
+/// Distorts the rendered eyes for the lenses and presents them on the headset.
+pub fn warp_swap() {
+    #[cfg(feature = "vr")]
+    {
+        // Safety: Kinc initializes the VR interface when it creates the window
+        unsafe { kinc_vr_interface_warp_swap() }
+    }
+}
+
+pub fn set_tracking_origin(origin: TrackingOrigin) {
+    #[cfg(feature = "vr")]
+    {
+        // Safety: Kinc initializes the VR interface when it creates the window
+        unsafe { kinc_vr_interface_update_tracking_origin(origin.into()) }
+    }
+    #[cfg(not(feature = "vr"))]
+    {
+        let _ = origin;
+    }
+}
+
+/// Makes the current head pose the new origin.
+pub fn reset_hmd_pose() {
+    #[cfg(feature = "vr")]
+    {
+        // Safety: Kinc initializes the VR interface when it creates the window
+        unsafe { kinc_vr_interface_reset_hmd_pose() }
+    }
+}
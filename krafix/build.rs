@@ -1,4 +1,4 @@
-use std::env;
+use std::{env, path::Path};
 
 fn find_files_rec(path: String, cb: &mut dyn FnMut(&str)) {
     for entry in std::fs::read_dir(path).unwrap() {
@@ -91,6 +91,10 @@ fn main() {
     ] {
         files(&format!("krafix/{}", pat));
     }
+    // Small additions to krafix' API, see shim/shim.h
+    println!("cargo:rerun-if-changed=shim");
+    files("shim/compile.cpp");
+    files("shim/log.cpp");
 
     if target_is_window {
         files("krafix/glslang/glslang/OSDependent/Windows/**");
@@ -104,6 +108,14 @@ fn main() {
     builder.cpp(true);
     builder.flag_if_supported("-std=c++11");
 
+    // Routes everything krafix and glslang print through shim/log.cpp
+    let log = Path::new(&env::var("CARGO_MANIFEST_DIR").unwrap()).join("shim/log.h");
+    if builder.get_compiler().is_like_msvc() {
+        builder.flag(format!("/FI{}", log.display()).as_str());
+    } else {
+        builder.flag("-include").flag(log.to_str().unwrap());
+    }
+
     for define in defines {
         builder.define(define, None);
     }
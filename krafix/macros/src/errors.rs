@@ -0,0 +1,49 @@
+use krafix::CompileError;
+use proc_macro2::Span;
+use syn::LitStr;
+
+/// The span of a line inside a string literal.
+///
+/// Spans inside literals are only available on nightly compilers and for literals without escapes,
+/// otherwise the whole literal is used.
+pub(crate) fn line_span(source: &LitStr, line: usize) -> Span {
+    let token = source.token();
+    let repr = token.to_string();
+    let content_start = match repr.find('"') {
+        Some(quote) if repr.starts_with('r') || !repr.contains('\\') => quote + 1,
+        _ => return source.span(),
+    };
+    let content = &repr[content_start..];
+    let start: usize = content
+        .split_inclusive('\n')
+        .take(line.saturating_sub(1))
+        .map(str::len)
+        .sum();
+    let end = start + content[start..].find(['\n', '"']).unwrap_or(0);
+    token
+        .subspan(content_start + start..content_start + end)
+        .unwrap_or_else(|| source.span())
+}
+
+/// Turns a compile error into errors pointing into the macro input,
+/// `locate` maps a line of the shader source to a span and a description of where that line is.
+pub(crate) fn compile_error(
+    error: &CompileError,
+    fallback: Span,
+    locate: impl Fn(usize) -> (Span, String),
+) -> syn::Error {
+    let mut errors = error.messages().iter().map(|message| match message.line {
+        Some(line) => {
+            let (span, location) = locate(line);
+            syn::Error::new(span, format!("{}: {}", location, message.text))
+        }
+        None => syn::Error::new(fallback, &message.text),
+    });
+    match errors.next() {
+        Some(mut first) => {
+            first.extend(errors);
+            first
+        }
+        None => syn::Error::new(fallback, error),
+    }
+}
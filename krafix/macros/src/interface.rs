@@ -0,0 +1,347 @@
+//! The vertex and uniform structs generated by `shader_interface!`.
+
+use std::ffi::CString;
+
+use krafix::reflect::{DataType, Reflection};
+use proc_macro2::{Ident, Literal, Span, TokenStream};
+use quote::{format_ident, quote};
+use syn::{Path, Result};
+
+/// A `#[repr(C)]` struct with a field per attribute in location order,
+/// and a `structure()` function returning the matching `VertexStructure`.
+pub(crate) fn vertex_struct(
+    krate: &Path,
+    name: &Ident,
+    reflection: &Reflection,
+) -> Result<TokenStream> {
+    let mut names = Names::default();
+    let mut fields = Vec::new();
+    let mut elements = Vec::new();
+    for attribute in &reflection.attributes {
+        let (ty, data) = vertex_type(krate, attribute.ty).ok_or_else(|| {
+            syn::Error::new(
+                name.span(),
+                format!(
+                    "attribute `{}` is a {:?}, which has no vertex data format",
+                    attribute.name, attribute.ty
+                ),
+            )
+        })?;
+        let field = names.field(name, "attribute", &attribute.name)?;
+        let c_name = c_string(&attribute.name);
+        fields.push(quote!(pub #field: #ty));
+        elements.push(quote!(.add(#c_name, #krate::g4::VertexData::#data)));
+    }
+
+    Ok(quote! {
+        #[repr(C)]
+        #[derive(Copy, Clone, Debug, PartialEq)]
+        pub struct #name {
+            #(#fields,)*
+        }
+
+        impl #name {
+            /// The vertex structure matching the fields of this struct.
+            pub fn structure() -> #krate::g4::VertexStructure<'static> {
+                #krate::g4::VertexStructureBuilder::new()
+                    #(#elements)*
+                    .build()
+            }
+        }
+    })
+}
+
+/// A struct with the `ConstantLocation` of every uniform and the `TextureUnit` of every sampler,
+/// along with typed setters for them.
+pub(crate) fn uniform_struct(
+    krate: &Path,
+    name: &Ident,
+    reflection: &Reflection,
+) -> Result<TokenStream> {
+    let mut names = Names::default();
+    let mut fields = Vec::new();
+    let mut lookups = Vec::new();
+    let mut setters = Vec::new();
+    for uniform in &reflection.uniforms {
+        if uniform.name.is_empty() {
+            continue;
+        }
+        let field = names.field(name, "uniform", &uniform.name)?;
+        let c_name = c_string(&uniform.name);
+        fields.push(quote!(pub #field: #krate::g4::ConstantLocation));
+        lookups.push(quote!(#field: pipeline.get_constant_location(#c_name)));
+
+        let setter = format_ident!("set_{}", snake_case(&uniform.name));
+        let (value, call) = match (uniform.ty, uniform.count) {
+            (DataType::Bool, 1) => (quote!(bool), quote!(set_bool)),
+            (DataType::Int, 1) => (quote!(i32), quote!(set_int)),
+            (DataType::Float, 1) => (quote!(f32), quote!(set_float)),
+            (DataType::Vec2, 1) => (quote!(#krate::math::Vec2), quote!(set_float2)),
+            (DataType::Vec3, 1) => (quote!(#krate::math::Vec3), quote!(set_float3)),
+            (DataType::Vec4, 1) => (quote!(#krate::math::Vec4), quote!(set_float4)),
+            (DataType::Mat3, 1) => (quote!(&#krate::math::Mat3), quote!(set_matrix3)),
+            (DataType::Mat4, 1) => (quote!(&#krate::math::Mat4), quote!(set_matrix4)),
+            (DataType::Float | DataType::Vec2 | DataType::Vec3 | DataType::Vec4, _) => {
+                (quote!(&[f32]), quote!(set_floats))
+            }
+            // Structs and the like only get their location
+            _ => continue,
+        };
+        setters.push(quote! {
+            pub fn #setter(&self, pass: &mut #krate::g4::RenderPass<'_>, value: #value) {
+                pass.#call(self.#field, value)
+            }
+        });
+    }
+    for sampler in &reflection.samplers {
+        if sampler.name.is_empty() {
+            continue;
+        }
+        let field = names.field(name, "sampler", &sampler.name)?;
+        let c_name = c_string(&sampler.name);
+        fields.push(quote!(pub #field: #krate::g4::TextureUnit));
+        lookups.push(quote!(#field: pipeline.get_texture_unit(#c_name)));
+
+        let setter = format_ident!("set_{}", snake_case(&sampler.name));
+        let (value, call) = match sampler.ty {
+            DataType::Sampler2DArray => {
+                (quote!(&#krate::g4::TextureArray), quote!(set_texture_array))
+            }
+            // Cube maps can be textures or render targets
+            DataType::SamplerCube => (quote!(&impl #krate::g4::Sampled), quote!(set_sampled)),
+            _ => (quote!(&#krate::g4::Texture), quote!(set_texture)),
+        };
+        setters.push(quote! {
+            pub fn #setter(&self, pass: &mut #krate::g4::RenderPass<'_>, value: #value) {
+                pass.#call(self.#field, value)
+            }
+        });
+    }
+
+    Ok(quote! {
+        #[derive(Copy, Clone)]
+        pub struct #name {
+            #(#fields,)*
+        }
+
+        impl #name {
+            /// Looks up the uniforms and samplers in a pipeline using this shader.
+            pub fn new(pipeline: &#krate::g4::Pipeline) -> Self {
+                Self {
+                    #(#lookups,)*
+                }
+            }
+
+            #(#setters)*
+        }
+    })
+}
+
+fn vertex_type(krate: &Path, ty: DataType) -> Option<(TokenStream, Ident)> {
+    let (ty, data) = match ty {
+        DataType::Float => (quote!(f32), "F32_1X"),
+        DataType::Vec2 => (quote!(#krate::math::Vec2), "F32_2X"),
+        DataType::Vec3 => (quote!(#krate::math::Vec3), "F32_3X"),
+        DataType::Vec4 => (quote!(#krate::math::Vec4), "F32_4X"),
+        DataType::Mat4 => (quote!(#krate::math::Mat4), "F32_4X4"),
+        DataType::Int => (quote!(i32), "I32_1X"),
+        DataType::IVec2 => (quote!([i32; 2]), "I32_2X"),
+        DataType::IVec3 => (quote!([i32; 3]), "I32_3X"),
+        DataType::IVec4 => (quote!([i32; 4]), "I32_4X"),
+        DataType::UInt => (quote!(u32), "U32_1X"),
+        DataType::UVec2 => (quote!([u32; 2]), "U32_2X"),
+        DataType::UVec3 => (quote!([u32; 3]), "U32_3X"),
+        DataType::UVec4 => (quote!([u32; 4]), "U32_4X"),
+        _ => return None,
+    };
+    Some((ty, Ident::new(data, Span::call_site())))
+}
+
+fn c_string(name: &str) -> Literal {
+    Literal::c_string(&CString::new(name).unwrap_or_default())
+}
+
+/// `modelViewMatrix` becomes `model_view_matrix` and `MVPMatrix` becomes `mvp_matrix`.
+fn snake_case(name: &str) -> String {
+    let chars: Vec<char> = name.chars().collect();
+    let mut snake = String::with_capacity(name.len() + 4);
+    for (i, &c) in chars.iter().enumerate() {
+        if c.is_uppercase() && i > 0 {
+            let previous = chars[i - 1];
+            let next_lower = chars.get(i + 1).is_some_and(|next| next.is_lowercase());
+            // Starts a word after a lowercase letter or digit, or ends an acronym like `MVP` in `MVPMatrix`
+            if previous.is_lowercase()
+                || previous.is_ascii_digit()
+                || (previous.is_uppercase() && next_lower)
+            {
+                snake.push('_');
+            }
+        }
+        snake.extend(c.to_lowercase());
+    }
+    snake
+}
+
+/// The snake case name as a field, with an underscore appended to Rust keywords.
+fn field_name(name: &str) -> Ident {
+    let snake = snake_case(name);
+    match syn::parse_str::<Ident>(&snake) {
+        Ok(ident) if !NEWER_KEYWORDS.contains(&snake.as_str()) => ident,
+        _ => format_ident!("{}_", snake),
+    }
+}
+
+/// Keywords of the 2018 and 2024 editions, which syn still parses as identifiers.
+const NEWER_KEYWORDS: [&str; 5] = ["async", "await", "dyn", "try", "gen"];
+
+/// The field names of a generated struct, which have to stay unique after the conversion to snake case.
+#[derive(Default)]
+struct Names(Vec<(Ident, String)>);
+
+impl Names {
+    /// The field for the shader variable `original`, which is an error if another variable got the same one.
+    fn field(&mut self, item: &Ident, kind: &str, original: &str) -> Result<Ident> {
+        let field = field_name(original);
+        let variable = format!("{} `{}`", kind, original);
+        if let Some((_, other)) = self.0.iter().find(|(existing, _)| *existing == field) {
+            return Err(syn::Error::new(
+                item.span(),
+                format!(
+                    "{} and {} would both become the field `{}` of `{}`",
+                    other, variable, field, item
+                ),
+            ));
+        }
+        self.0.push((field.clone(), variable));
+        Ok(field)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use krafix::reflect::{Attribute, Sampler, Uniform};
+
+    use super::*;
+
+    fn krate() -> Path {
+        syn::parse_quote!(kinc)
+    }
+
+    fn attribute(name: &str, location: u32, ty: DataType) -> Attribute {
+        Attribute {
+            name: name.to_owned(),
+            location,
+            ty,
+        }
+    }
+
+    fn sampler(name: &str, ty: DataType) -> Sampler {
+        Sampler {
+            name: name.to_owned(),
+            binding: None,
+            set: None,
+            ty,
+        }
+    }
+
+    #[test]
+    fn converts_names_to_snake_case() {
+        for (name, snake) in [
+            ("pos", "pos"),
+            ("modelViewMatrix", "model_view_matrix"),
+            ("ModelView", "model_view"),
+            ("MVP", "mvp"),
+            ("MVPMatrix", "mvp_matrix"),
+            ("lightMVP", "light_mvp"),
+            ("texCoord0", "tex_coord0"),
+            ("light2Color", "light2_color"),
+            ("already_snake", "already_snake"),
+            ("bone_Weights", "bone_weights"),
+        ] {
+            assert_eq!(snake_case(name), snake, "{}", name);
+        }
+    }
+
+    #[test]
+    fn escapes_keywords() {
+        for (name, field) in [
+            ("type", "type_"),
+            ("match", "match_"),
+            ("self", "self_"),
+            ("Self", "self_"),
+            ("crate", "crate_"),
+            ("async", "async_"),
+            ("gen", "gen_"),
+            ("union", "union"),
+            ("color", "color"),
+        ] {
+            assert_eq!(field_name(name).to_string(), field, "{}", name);
+        }
+    }
+
+    #[test]
+    fn rejects_attributes_with_the_same_field() {
+        let reflection = Reflection {
+            attributes: vec![
+                attribute("fooBar", 0, DataType::Vec2),
+                attribute("foo_bar", 1, DataType::Vec2),
+            ],
+            ..Reflection::default()
+        };
+        let error = vertex_struct(&krate(), &format_ident!("Vertex"), &reflection)
+            .err()
+            .unwrap();
+        assert_eq!(
+            error.to_string(),
+            "attribute `fooBar` and attribute `foo_bar` would both become the field `foo_bar` of `Vertex`"
+        );
+    }
+
+    #[test]
+    fn rejects_uniforms_and_samplers_with_the_same_field() {
+        let reflection = Reflection {
+            uniforms: vec![Uniform {
+                name: "type".to_owned(),
+                ty: DataType::Float,
+                count: 1,
+            }],
+            samplers: vec![sampler("type_", DataType::Sampler2D)],
+            ..Reflection::default()
+        };
+        let error = uniform_struct(&krate(), &format_ident!("Uniforms"), &reflection)
+            .err()
+            .unwrap();
+        assert_eq!(
+            error.to_string(),
+            "uniform `type` and sampler `type_` would both become the field `type_` of `Uniforms`"
+        );
+    }
+
+    #[test]
+    fn cube_samplers_accept_anything_sampled() {
+        let reflection = Reflection {
+            samplers: vec![
+                sampler("sky", DataType::SamplerCube),
+                sampler("albedo", DataType::Sampler2D),
+            ],
+            ..Reflection::default()
+        };
+        let tokens = uniform_struct(&krate(), &format_ident!("Uniforms"), &reflection)
+            .unwrap()
+            .to_string();
+        assert!(
+            tokens.contains(
+                "value : & impl kinc :: g4 :: Sampled) { pass . set_sampled (self . sky , value) }"
+            ),
+            "{}",
+            tokens
+        );
+        assert!(
+            tokens.contains(
+                "value : & kinc :: g4 :: Texture) { pass . set_texture (self . albedo , value) }"
+            ),
+            "{}",
+            tokens
+        );
+    }
+}
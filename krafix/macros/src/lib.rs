@@ -0,0 +1,244 @@
+use std::path::Path;
+
+use krafix::{include, CompileError, ShaderKind, Target};
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use syn::{
+    parse::{Parse, ParseStream, Result},
+    parse_macro_input, Ident, LitByteStr, LitStr, Token,
+};
+
+mod errors;
+mod interface;
+mod options;
+mod reflection;
+
+use options::Options;
+
+struct Shader {
+    kind: ShaderKind,
+    lang: Target,
+    source: LitStr,
+    options: Options,
+}
+
+impl Parse for Shader {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let kind = input.parse::<Ident>()?;
+        input.parse::<Token![,]>()?;
+        let lang = options::parse_target(input)?;
+        input.parse::<Token![,]>()?;
+        let source: LitStr = input.parse()?;
+        let options = Options::parse(input)?;
+
+        let kind = ShaderKind::from_name(&kind.to_string()).ok_or_else(|| {
+            syn::Error::new(
+                kind.span(),
+                "unknown shader kind, expected one of `vertex`, `fragment`, `geometry`, \
+                 `tess_control`, `tess_eval` or `compute`",
+            )
+        })?;
+
+        Ok(Shader {
+            kind,
+            lang,
+            source,
+            options,
+        })
+    }
+}
+
+impl Shader {
+    fn compile_options(&self) -> krafix::Options {
+        let mut options = krafix::Options::new(self.kind, self.options.target.unwrap_or(self.lang));
+        if let Some(version) = self.options.version {
+            options = options.version(version);
+        }
+        if let Some(system) = self.options.system {
+            options = options.system(system);
+        }
+        for (name, value) in &self.options.defines {
+            options = options.define(name, value);
+        }
+        options
+    }
+
+    fn krate(&self) -> syn::Path {
+        self.options
+            .krate
+            .clone()
+            .unwrap_or_else(|| syn::parse_quote!(::kinc))
+    }
+}
+
+#[proc_macro]
+pub fn compile_shader(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let shader: Shader = parse_macro_input!(input as Shader);
+    expression_only(&shader)
+        .and_then(|()| compile(&shader, &shader.source.value(), inline_location(&shader)))
+        .unwrap_or_else(|error| error.to_compile_error())
+        .into()
+}
+
+/// Like [`compile_shader!`], but reads the shader from a file relative to `CARGO_MANIFEST_DIR`.
+///
+/// `#include "file"` directives are resolved relative to the including file,
+/// and the crate is rebuilt when any of the files changes.
+#[proc_macro]
+pub fn compile_shader_file(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let shader: Shader = parse_macro_input!(input as Shader);
+    compile_file(&shader)
+        .unwrap_or_else(|error| error.to_compile_error())
+        .into()
+}
+
+/// Generates items from a shader's reflection data, named by the arguments
+/// `bytecode = NAME` for a `&[u8]` constant with the compiled shader,
+/// `vertex = Name` for a `#[repr(C)]` vertex struct with its `VertexStructure`,
+/// and `uniforms = Name` for a struct with the locations of the uniforms and typed setters.
+#[proc_macro]
+pub fn shader_interface(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let shader: Shader = parse_macro_input!(input as Shader);
+    interface(&shader, &shader.source.value(), inline_location(&shader))
+        .unwrap_or_else(|error| error.to_compile_error())
+        .into()
+}
+
+/// Like [`shader_interface!`], but reads the shader from a file like [`compile_shader_file!`].
+#[proc_macro]
+pub fn shader_interface_file(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let shader: Shader = parse_macro_input!(input as Shader);
+    interface_file(&shader)
+        .unwrap_or_else(|error| error.to_compile_error())
+        .into()
+}
+
+fn expression_only(shader: &Shader) -> Result<()> {
+    match shader.options.item_name() {
+        Some(name) => Err(syn::Error::new(
+            name.span(),
+            "`bytecode`, `vertex` and `uniforms` are only supported by `shader_interface!`",
+        )),
+        None => Ok(()),
+    }
+}
+
+fn inline_location(shader: &Shader) -> impl Fn(usize) -> (Span, String) + '_ {
+    |line| {
+        (
+            errors::line_span(&shader.source, line),
+            format!("shader line {}", line),
+        )
+    }
+}
+
+fn file_location<'a>(
+    shader: &'a Shader,
+    expanded: &'a include::Expanded,
+) -> impl Fn(usize) -> (Span, String) + 'a {
+    |line| {
+        let location = match expanded.origin(line) {
+            Some((file, line)) => format!("{}:{}", file.display(), line),
+            None => format!("shader line {}", line),
+        };
+        (shader.source.span(), location)
+    }
+}
+
+fn load_file(shader: &Shader) -> Result<include::Expanded> {
+    let manifest_dir = std::env::var_os("CARGO_MANIFEST_DIR").unwrap_or_default();
+    let path = Path::new(&manifest_dir).join(shader.source.value());
+    include::expand(&path).map_err(|error| syn::Error::new(shader.source.span(), error))
+}
+
+/// `include_bytes!` for every file, which makes cargo rebuild when one of them changes.
+fn track_files(expanded: &include::Expanded) -> TokenStream {
+    let files = expanded
+        .files
+        .iter()
+        .map(|file| LitStr::new(&file.to_string_lossy(), Span::call_site()));
+    quote! {
+        #(const _: &[u8] = include_bytes!(#files);)*
+    }
+}
+
+fn compile_file(shader: &Shader) -> Result<TokenStream> {
+    expression_only(shader)?;
+    let expanded = load_file(shader)?;
+    let output = compile(shader, &expanded.source, file_location(shader, &expanded))?;
+    let tracking = track_files(&expanded);
+    Ok(quote! {
+        {
+            #tracking
+            #output
+        }
+    })
+}
+
+fn interface_file(shader: &Shader) -> Result<TokenStream> {
+    let expanded = load_file(shader)?;
+    let items = interface(shader, &expanded.source, file_location(shader, &expanded))?;
+    let tracking = track_files(&expanded);
+    Ok(quote! {
+        #tracking
+        #items
+    })
+}
+
+/// Compiles `source` into a byte string, or a `ReflectedShader` if reflection was asked for.
+fn compile(
+    shader: &Shader,
+    source: &str,
+    locate: impl Fn(usize) -> (Span, String),
+) -> Result<TokenStream> {
+    let options = shader.compile_options();
+    let error = |error: CompileError| errors::compile_error(&error, shader.source.span(), &locate);
+
+    let output = options.compile(source).map_err(error)?;
+    let byte_string = LitByteStr::new(&output, Span::call_site());
+    if !shader.options.reflect {
+        return Ok(quote! {
+            #byte_string
+        });
+    }
+
+    let reflection = options.reflect(source).map_err(error)?;
+    Ok(reflection::reflected_shader(
+        &shader.krate(),
+        quote!(#byte_string),
+        &reflection,
+    ))
+}
+
+fn interface(
+    shader: &Shader,
+    source: &str,
+    locate: impl Fn(usize) -> (Span, String),
+) -> Result<TokenStream> {
+    if shader.options.item_name().is_none() {
+        return Err(syn::Error::new(
+            Span::call_site(),
+            "expected at least one of `bytecode = NAME`, `vertex = Name` or `uniforms = Name`",
+        ));
+    }
+    let options = shader.compile_options();
+    let error = |error: CompileError| errors::compile_error(&error, shader.source.span(), &locate);
+    let reflection = options.reflect(source).map_err(error)?;
+    let krate = shader.krate();
+
+    let mut items = TokenStream::new();
+    if let Some(name) = &shader.options.bytecode {
+        let output = options.compile(source).map_err(error)?;
+        let byte_string = LitByteStr::new(&output, Span::call_site());
+        items.extend(quote! {
+            pub const #name: &[u8] = #byte_string;
+        });
+    }
+    if let Some(name) = &shader.options.vertex {
+        items.extend(interface::vertex_struct(&krate, name, &reflection)?);
+    }
+    if let Some(name) = &shader.options.uniforms {
+        items.extend(interface::uniform_struct(&krate, name, &reflection)?);
+    }
+    Ok(items)
+}
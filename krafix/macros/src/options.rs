@@ -0,0 +1,128 @@
+use krafix::{System, Target};
+use syn::{
+    braced,
+    parse::{ParseStream, Result},
+    punctuated::Punctuated,
+    Ident, LitBool, LitInt, LitStr, Path, Token,
+};
+
+/// The optional named arguments after the shader source,
+/// `version = 330, target = spirv, system = android, defines = { "USE_FOG" => "1" }, reflect = true`.
+pub(crate) struct Options {
+    pub version: Option<i32>,
+    pub target: Option<Target>,
+    pub system: Option<System>,
+    pub defines: Vec<(String, String)>,
+    pub reflect: bool,
+    /// The path of the kinc crate for the reflection types, kinc's wrapper macros pass `$crate`.
+    pub krate: Option<Path>,
+    /// The names of the items generated by `shader_interface!`.
+    pub bytecode: Option<Ident>,
+    pub vertex: Option<Ident>,
+    pub uniforms: Option<Ident>,
+}
+
+struct Define {
+    name: LitStr,
+    value: LitStr,
+}
+
+impl Options {
+    pub fn parse(input: ParseStream) -> Result<Self> {
+        let mut options = Options {
+            version: None,
+            target: None,
+            system: None,
+            defines: Vec::new(),
+            reflect: false,
+            krate: None,
+            bytecode: None,
+            vertex: None,
+            uniforms: None,
+        };
+        let mut seen: Vec<String> = Vec::new();
+        while !input.is_empty() {
+            input.parse::<Token![,]>()?;
+            if input.is_empty() {
+                break;
+            }
+            let name = input.parse::<Ident>()?;
+            input.parse::<Token![=]>()?;
+            let key = name.to_string();
+            if seen.contains(&key) {
+                return Err(syn::Error::new(
+                    name.span(),
+                    format!("`{}` is given more than once", key),
+                ));
+            }
+            match key.as_str() {
+                "version" => options.version = Some(input.parse::<LitInt>()?.base10_parse()?),
+                "target" => options.target = Some(parse_target(input)?),
+                "system" => {
+                    let system = input.parse::<Ident>()?;
+                    options.system = Some(
+                        System::from_name(&system.to_string())
+                            .ok_or_else(|| syn::Error::new(system.span(), "unknown system"))?,
+                    );
+                }
+                "defines" => {
+                    let content;
+                    braced!(content in input);
+                    let defines = Punctuated::<Define, Token![,]>::parse_terminated_with(
+                        &content,
+                        Define::parse,
+                    )?;
+                    for define in defines {
+                        if define.name.value().contains(char::is_whitespace) {
+                            return Err(syn::Error::new(
+                                define.name.span(),
+                                "define names must not contain whitespace",
+                            ));
+                        }
+                        options
+                            .defines
+                            .push((define.name.value(), define.value.value()));
+                    }
+                }
+                "reflect" => options.reflect = input.parse::<LitBool>()?.value,
+                "krate" => options.krate = Some(input.parse()?),
+                "bytecode" => options.bytecode = Some(input.parse()?),
+                "vertex" => options.vertex = Some(input.parse()?),
+                "uniforms" => options.uniforms = Some(input.parse()?),
+                _ => {
+                    return Err(syn::Error::new(
+                        name.span(),
+                        "unknown argument, expected one of `version`, `target`, `system`, \
+                         `defines`, `reflect`, `bytecode`, `vertex` or `uniforms`",
+                    ))
+                }
+            }
+            seen.push(key);
+        }
+        Ok(options)
+    }
+
+    /// The first argument naming an item, which only `shader_interface!` generates.
+    pub fn item_name(&self) -> Option<&Ident> {
+        self.bytecode
+            .as_ref()
+            .or(self.vertex.as_ref())
+            .or(self.uniforms.as_ref())
+    }
+}
+
+impl Define {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let name = input.parse()?;
+        input.parse::<Token![=>]>()?;
+        let value = input.parse()?;
+        Ok(Define { name, value })
+    }
+}
+
+/// A target language like `spirv` or `essl`.
+pub(crate) fn parse_target(input: ParseStream) -> Result<Target> {
+    let target = input.parse::<Ident>()?;
+    Target::from_name(&target.to_string())
+        .ok_or_else(|| syn::Error::new(target.span(), "unknown target language"))
+}
@@ -0,0 +1,136 @@
+use std::ffi::CString;
+
+use krafix::reflect::{DataType, Reflection};
+use proc_macro2::{Ident, Literal, Span, TokenStream};
+use quote::quote;
+use syn::Path;
+
+/// A `kinc::reflection::ReflectedShader` with `bytecode` and the reflection data.
+pub(crate) fn reflected_shader(
+    krate: &Path,
+    bytecode: TokenStream,
+    reflection: &Reflection,
+) -> TokenStream {
+    let ty = |ty: DataType| {
+        let variant = Ident::new(data_type(ty), Span::call_site());
+        quote!(#krate::reflection::DataType::#variant)
+    };
+    let name = |name: &str| Literal::c_string(&CString::new(name).unwrap_or_default());
+
+    let attributes = reflection.attributes.iter().map(|attribute| {
+        let name = name(&attribute.name);
+        let location = attribute.location;
+        let ty = ty(attribute.ty);
+        quote! {
+            #krate::reflection::Attribute { name: #name, location: #location, ty: #ty }
+        }
+    });
+    let uniforms = reflection.uniforms.iter().map(|uniform| {
+        let name = name(&uniform.name);
+        let ty = ty(uniform.ty);
+        let count = uniform.count;
+        quote! {
+            #krate::reflection::Uniform { name: #name, ty: #ty, count: #count }
+        }
+    });
+    let samplers = reflection.samplers.iter().map(|sampler| {
+        let name = name(&sampler.name);
+        let binding = option(sampler.binding.map(|binding| quote!(#binding)));
+        let ty = ty(sampler.ty);
+        quote! {
+            #krate::reflection::Sampler { name: #name, binding: #binding, ty: #ty }
+        }
+    });
+    let workgroup_size = option(
+        reflection
+            .workgroup_size
+            .map(|[x, y, z]| quote!([#x, #y, #z])),
+    );
+
+    quote! {
+        #krate::reflection::ReflectedShader {
+            bytecode: #bytecode,
+            reflection: #krate::reflection::Reflection {
+                attributes: &[#(#attributes),*],
+                uniforms: &[#(#uniforms),*],
+                samplers: &[#(#samplers),*],
+                workgroup_size: #workgroup_size,
+            },
+        }
+    }
+}
+
+/// The name of the `kinc::reflection::DataType` variant.
+fn data_type(ty: DataType) -> &'static str {
+    match ty {
+        DataType::Bool => "Bool",
+        DataType::Int => "Int",
+        DataType::IVec2 => "IVec2",
+        DataType::IVec3 => "IVec3",
+        DataType::IVec4 => "IVec4",
+        DataType::UInt => "UInt",
+        DataType::UVec2 => "UVec2",
+        DataType::UVec3 => "UVec3",
+        DataType::UVec4 => "UVec4",
+        DataType::Float => "Float",
+        DataType::Vec2 => "Vec2",
+        DataType::Vec3 => "Vec3",
+        DataType::Vec4 => "Vec4",
+        DataType::Mat2 => "Mat2",
+        DataType::Mat3 => "Mat3",
+        DataType::Mat4 => "Mat4",
+        DataType::Sampler2D => "Sampler2D",
+        DataType::Sampler2DArray => "Sampler2DArray",
+        DataType::Sampler3D => "Sampler3D",
+        DataType::SamplerCube => "SamplerCube",
+        DataType::Struct => "Struct",
+        DataType::Other => "Other",
+    }
+}
+
+fn option(value: Option<TokenStream>) -> TokenStream {
+    match value {
+        Some(value) => quote!(::core::option::Option::Some(#value)),
+        None => quote!(::core::option::Option::None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use krafix::reflect::{Attribute, Sampler, Uniform};
+
+    use super::*;
+
+    #[test]
+    fn refers_to_kinc_data_types() {
+        let reflection = Reflection {
+            attributes: vec![Attribute {
+                name: "pos".to_owned(),
+                location: 0,
+                ty: DataType::Vec3,
+            }],
+            uniforms: vec![Uniform {
+                name: "bones".to_owned(),
+                ty: DataType::Mat4,
+                count: 4,
+            }],
+            samplers: vec![Sampler {
+                name: "sky".to_owned(),
+                binding: Some(1),
+                set: Some(0),
+                ty: DataType::SamplerCube,
+            }],
+            workgroup_size: None,
+        };
+        let tokens =
+            reflected_shader(&syn::parse_quote!(kinc), quote!(&[]), &reflection).to_string();
+        for variant in ["Vec3", "Mat4", "SamplerCube"] {
+            assert!(
+                tokens.contains(&format!("kinc :: reflection :: DataType :: {}", variant)),
+                "{}",
+                tokens
+            );
+        }
+        assert!(tokens.contains("Some (1u32)"), "{}", tokens);
+    }
+}
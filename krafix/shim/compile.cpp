@@ -0,0 +1,190 @@
+#include "shim.h"
+
+#include <stdio.h>
+
+#ifdef _WIN32
+#include <windows.h>
+#else
+#include <setjmp.h>
+#include <signal.h>
+#include <sys/mman.h>
+#endif
+
+extern "C" int krafix_compile(const char *source, char *output, int *length, const char *targetlang, const char *system, const char *shadertype,
+                              int shaderversion);
+
+namespace {
+	// krafix_compile has no way to report that its output didn't fit, so the buffer is big enough for any shader
+	// and followed by inaccessible memory. Pages are only backed by memory once krafix writes to them, and a write
+	// past the end faults in the guard, which is turned into an error below instead of a crash.
+	const size_t capacity = 256 * 1024 * 1024;
+	const size_t guard = 64 * 1024;
+
+	// The buffer of the compilation running on this thread, read by the fault handlers
+	struct Compilation {
+		char *buffer;
+		bool overflowed;
+#ifndef _WIN32
+		sigjmp_buf overflow;
+#endif
+	};
+
+	thread_local Compilation *current = nullptr;
+
+	bool in_guard(const Compilation *compilation, const char *address) {
+		return compilation != nullptr && address >= compilation->buffer + capacity && address < compilation->buffer + capacity + guard;
+	}
+
+#ifdef _WIN32
+	// Only reserves the address space, handle_fault commits pages as krafix reaches them
+	char *reserve() {
+		return static_cast<char *>(VirtualAlloc(nullptr, capacity + guard, MEM_RESERVE, PAGE_NOACCESS));
+	}
+
+	void release(char *memory) {
+		VirtualFree(memory, 0, MEM_RELEASE);
+	}
+
+	LONG handle_fault(EXCEPTION_POINTERS *exception) {
+		Compilation *compilation = current;
+		EXCEPTION_RECORD *record = exception->ExceptionRecord;
+		if (compilation == nullptr || record->ExceptionCode != EXCEPTION_ACCESS_VIOLATION || record->NumberParameters < 2) {
+			return EXCEPTION_CONTINUE_SEARCH;
+		}
+		char *address = reinterpret_cast<char *>(record->ExceptionInformation[1]);
+		if (address >= compilation->buffer && address < compilation->buffer + capacity) {
+			// Commits a megabyte at a time, so that long outputs don't fault on every page
+			const size_t chunk = 1024 * 1024;
+			size_t offset = static_cast<size_t>(address - compilation->buffer) / chunk * chunk;
+			size_t size = capacity - offset < chunk ? capacity - offset : chunk;
+			if (VirtualAlloc(compilation->buffer + offset, size, MEM_COMMIT, PAGE_READWRITE) != nullptr) {
+				return EXCEPTION_CONTINUE_EXECUTION;
+			}
+			return EXCEPTION_CONTINUE_SEARCH;
+		}
+		return in_guard(compilation, address) ? EXCEPTION_EXECUTE_HANDLER : EXCEPTION_CONTINUE_SEARCH;
+	}
+
+#ifndef _MSC_VER
+	// Without __try, pages are committed from a vectored handler, and a write into the guard still ends the process
+	LONG CALLBACK commit_on_demand(EXCEPTION_POINTERS *exception) {
+		LONG result = handle_fault(exception);
+		return result == EXCEPTION_CONTINUE_EXECUTION ? result : EXCEPTION_CONTINUE_SEARCH;
+	}
+#endif
+
+	int run(Compilation &compilation, const char *source, int *length, const char *targetlang, const char *system, const char *shadertype,
+	        int shaderversion) {
+		current = &compilation;
+#ifdef _MSC_VER
+		int result;
+		__try {
+			result = krafix_compile(source, compilation.buffer, length, targetlang, system, shadertype, shaderversion);
+		}
+		__except (handle_fault(GetExceptionInformation())) {
+			compilation.overflowed = true;
+			result = -1;
+		}
+#else
+		static PVOID handler = AddVectoredExceptionHandler(1, commit_on_demand);
+		(void)handler;
+		int result = krafix_compile(source, compilation.buffer, length, targetlang, system, shadertype, shaderversion);
+#endif
+		current = nullptr;
+		return result;
+	}
+#else
+	char *reserve() {
+		void *memory = mmap(nullptr, capacity + guard, PROT_READ | PROT_WRITE, MAP_PRIVATE | MAP_ANONYMOUS | MAP_NORESERVE, -1, 0);
+		if (memory == MAP_FAILED) {
+			return nullptr;
+		}
+		if (mprotect(static_cast<char *>(memory) + capacity, guard, PROT_NONE) != 0) {
+			munmap(memory, capacity + guard);
+			return nullptr;
+		}
+		return static_cast<char *>(memory);
+	}
+
+	void release(char *memory) {
+		munmap(memory, capacity + guard);
+	}
+
+	struct sigaction previous_segv;
+	struct sigaction previous_bus;
+
+	void handle_fault(int signal, siginfo_t *info, void *context) {
+		Compilation *compilation = current;
+		if (in_guard(compilation, static_cast<const char *>(info->si_addr))) {
+			// krafix writes its output last, so skipping its frames only leaks what they allocated
+			siglongjmp(compilation->overflow, 1);
+		}
+		// Any other fault goes to whoever handled it before, like Rust's stack overflow check
+		const struct sigaction &previous = signal == SIGBUS ? previous_bus : previous_segv;
+		if (previous.sa_flags & SA_SIGINFO) {
+			previous.sa_sigaction(signal, info, context);
+		}
+		else if (previous.sa_handler != SIG_DFL && previous.sa_handler != SIG_IGN) {
+			previous.sa_handler(signal);
+		}
+		else {
+			// Returning retries the access, which now ends the process as usual
+			::signal(signal, SIG_DFL);
+		}
+	}
+
+	bool install_handler() {
+		struct sigaction action = {};
+		action.sa_sigaction = handle_fault;
+		action.sa_flags = SA_SIGINFO | SA_ONSTACK;
+		sigemptyset(&action.sa_mask);
+		// macOS reports accesses to protected pages as SIGBUS
+		return sigaction(SIGSEGV, &action, &previous_segv) == 0 && sigaction(SIGBUS, &action, &previous_bus) == 0;
+	}
+
+	int run(Compilation &compilation, const char *source, int *length, const char *targetlang, const char *system, const char *shadertype,
+	        int shaderversion) {
+		static bool installed = install_handler();
+		if (!installed) {
+			fprintf(stderr, "ERROR: could not install the handler for oversized shader output\n");
+			return -1;
+		}
+		if (sigsetjmp(compilation.overflow, 1) != 0) {
+			current = nullptr;
+			compilation.overflowed = true;
+			return -1;
+		}
+		current = &compilation;
+		int result = krafix_compile(source, compilation.buffer, length, targetlang, system, shadertype, shaderversion);
+		current = nullptr;
+		return result;
+	}
+#endif
+}
+
+extern "C" int krafix_rust_compile(const char *source, krafix_rust_write output, void *output_context, const char *targetlang, const char *system,
+                                   const char *shadertype, int shaderversion) {
+	Compilation compilation;
+	compilation.buffer = reserve();
+	compilation.overflowed = false;
+	if (compilation.buffer == nullptr) {
+		fprintf(stderr, "ERROR: could not reserve memory for the compiled shader\n");
+		return -1;
+	}
+	int length = static_cast<int>(capacity);
+	int result = run(compilation, source, &length, targetlang, system, shadertype, shaderversion);
+	if (compilation.overflowed) {
+		fprintf(stderr, "ERROR: the compiled shader is larger than %d MiB\n", static_cast<int>(capacity / (1024 * 1024)));
+	}
+	else if (result == 0) {
+		if (length < 0 || static_cast<size_t>(length) > capacity) {
+			fprintf(stderr, "ERROR: krafix reported an invalid output size of %d\n", length);
+			result = -1;
+		}
+		else {
+			output(output_context, reinterpret_cast<const uint8_t *>(compilation.buffer), static_cast<size_t>(length));
+		}
+	}
+	release(compilation.buffer);
+	return result;
+}
@@ -0,0 +1,108 @@
+#include "log.h"
+#include "shim.h"
+
+#include <string.h>
+
+#include <string>
+
+// log.h is force-included here as well, but this file forwards to the real functions
+#undef vfprintf
+#undef fprintf
+#undef vprintf
+#undef printf
+#undef fputs
+#undef puts
+#undef fputc
+#undef putchar
+
+namespace {
+	struct Sink {
+		krafix_rust_write write;
+		void *context;
+	};
+
+	thread_local Sink sink = {nullptr, nullptr};
+
+	bool captured(FILE *file) {
+		return sink.write != nullptr && (file == stdout || file == stderr);
+	}
+
+	int write(const char *text, size_t length) {
+		sink.write(sink.context, reinterpret_cast<const uint8_t *>(text), length);
+		return static_cast<int>(length);
+	}
+}
+
+extern "C" void krafix_rust_log_begin(krafix_rust_write write, void *context) {
+	sink.write = write;
+	sink.context = context;
+}
+
+extern "C" void krafix_rust_log_end(void) {
+	sink.write = nullptr;
+	sink.context = nullptr;
+}
+
+extern "C" int krafix_rust_vfprintf(FILE *file, const char *format, va_list args) {
+	if (!captured(file)) {
+		return vfprintf(file, format, args);
+	}
+	va_list copy;
+	va_copy(copy, args);
+	int length = vsnprintf(nullptr, 0, format, copy);
+	va_end(copy);
+	if (length <= 0) {
+		return length;
+	}
+	std::string text(static_cast<size_t>(length) + 1, '\0');
+	vsnprintf(&text[0], text.size(), format, args);
+	return write(text.data(), static_cast<size_t>(length));
+}
+
+extern "C" int krafix_rust_fprintf(FILE *file, const char *format, ...) {
+	va_list args;
+	va_start(args, format);
+	int length = krafix_rust_vfprintf(file, format, args);
+	va_end(args);
+	return length;
+}
+
+extern "C" int krafix_rust_vprintf(const char *format, va_list args) {
+	return krafix_rust_vfprintf(stdout, format, args);
+}
+
+extern "C" int krafix_rust_printf(const char *format, ...) {
+	va_list args;
+	va_start(args, format);
+	int length = krafix_rust_vfprintf(stdout, format, args);
+	va_end(args);
+	return length;
+}
+
+extern "C" int krafix_rust_fputs(const char *text, FILE *file) {
+	if (!captured(file)) {
+		return fputs(text, file);
+	}
+	return write(text, strlen(text));
+}
+
+extern "C" int krafix_rust_puts(const char *text) {
+	if (!captured(stdout)) {
+		return puts(text);
+	}
+	write(text, strlen(text));
+	return write("\n", 1);
+}
+
+extern "C" int krafix_rust_fputc(int c, FILE *file) {
+	if (!captured(file)) {
+		return fputc(c, file);
+	}
+	char character = static_cast<char>(c);
+	write(&character, 1);
+	return c;
+}
+
+extern "C" int krafix_rust_putchar(int c) {
+	return krafix_rust_fputc(c, stdout);
+}
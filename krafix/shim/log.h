@@ -0,0 +1,47 @@
+#pragma once
+
+// Force-included into every krafix and glslang source file by build.rs,
+// so that their console output can be captured per thread by krafix_rust_log_begin.
+
+#include <stdarg.h>
+#include <stdio.h>
+
+#ifdef __cplusplus
+#include <cstdio>
+
+extern "C" {
+#endif
+
+int krafix_rust_vfprintf(FILE *file, const char *format, va_list args);
+int krafix_rust_fprintf(FILE *file, const char *format, ...);
+int krafix_rust_vprintf(const char *format, va_list args);
+int krafix_rust_printf(const char *format, ...);
+int krafix_rust_fputs(const char *text, FILE *file);
+int krafix_rust_puts(const char *text);
+int krafix_rust_fputc(int c, FILE *file);
+int krafix_rust_putchar(int c);
+
+#ifdef __cplusplus
+}
+
+// For calls qualified with std::
+namespace std {
+	using ::krafix_rust_fprintf;
+	using ::krafix_rust_fputc;
+	using ::krafix_rust_fputs;
+	using ::krafix_rust_printf;
+	using ::krafix_rust_putchar;
+	using ::krafix_rust_puts;
+	using ::krafix_rust_vfprintf;
+	using ::krafix_rust_vprintf;
+}
+#endif
+
+#define vfprintf krafix_rust_vfprintf
+#define fprintf krafix_rust_fprintf
+#define vprintf krafix_rust_vprintf
+#define printf krafix_rust_printf
+#define fputs krafix_rust_fputs
+#define puts krafix_rust_puts
+#define fputc krafix_rust_fputc
+#define putchar krafix_rust_putchar
@@ -0,0 +1,28 @@
+#pragma once
+
+// Additions to krafix' C API that the Rust wrapper needs, compiled together with krafix in build.rs.
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+// Receives a chunk of data, context is passed through from whoever registered the callback.
+typedef void (*krafix_rust_write)(void *context, const uint8_t *data, size_t length);
+
+// Until krafix_rust_log_end, everything krafix and glslang print to stdout or stderr on the calling thread
+// is passed to write instead. Other threads keep printing normally.
+void krafix_rust_log_begin(krafix_rust_write write, void *context);
+void krafix_rust_log_end(void);
+
+// krafix_compile into a buffer owned by the shim, which is big enough for any output. On success the output
+// is passed to output in one piece, errors are printed like krafix' own. Output that doesn't fit fails the
+// compilation instead of writing past the buffer.
+int krafix_rust_compile(const char *source, krafix_rust_write output, void *output_context, const char *targetlang, const char *system,
+                        const char *shadertype, int shaderversion);
+
+#ifdef __cplusplus
+}
+#endif
@@ -0,0 +1,210 @@
+use std::path::PathBuf;
+
+use krafix::{System, Target};
+
+pub const USAGE: &str = "\
+Compiles GLSL shaders for the given targets and writes their reflection data as JSON.
+
+Usage: krafix [OPTIONS] --target <TARGET> --output <DIR> <INPUT>...
+
+Inputs are shader files or directories, which are searched recursively for files
+ending in .vert, .frag, .geom, .tesc, .tese or .comp.
+Shaders whose sources and options didn't change since the last run are skipped,
+and the outputs of shaders and targets that are gone are deleted.
+
+Options:
+  -t, --target <TARGET>   glsl, essl, hlsl, d3d9, d3d11, metal, spirv, agal, varjo or js,
+                          can be given several times or separated by commas
+  -o, --output <DIR>      Where to write the compiled shaders
+  -D <NAME[=VALUE]>       Defines a macro after the #version directive
+      --version <VERSION> The GLSL version of the targets [default: 300]
+      --system <SYSTEM>   windows, windowsapp, macos, linux, android, ios, tvos or html5
+                          [default: the host system]
+  -f, --force             Compiles every shader, even if it is up to date
+  -q, --quiet             Only prints errors
+  -h, --help              Prints this message
+";
+
+pub struct Args {
+    pub inputs: Vec<PathBuf>,
+    pub output: PathBuf,
+    pub targets: Vec<Target>,
+    pub defines: Vec<(String, String)>,
+    pub version: i32,
+    pub system: System,
+    pub force: bool,
+    pub quiet: bool,
+}
+
+/// What the command line asks for, `None` if it asks for the help text.
+pub fn parse(mut args: impl Iterator<Item = String>) -> Result<Option<Args>, String> {
+    let mut inputs = Vec::new();
+    let mut output = None;
+    let mut targets = Vec::new();
+    let mut defines = Vec::new();
+    let mut version = 300;
+    let mut system = System::host();
+    let mut force = false;
+    let mut quiet = false;
+
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| {
+            args.next()
+                .ok_or_else(|| format!("`{}` expects a value", name))
+        };
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(None),
+            "-t" | "--target" => {
+                for name in value(&arg)?.split(',') {
+                    let target = Target::from_name(name.trim())
+                        .ok_or_else(|| format!("unknown target `{}`", name))?;
+                    if !targets.contains(&target) {
+                        targets.push(target);
+                    }
+                }
+            }
+            "-o" | "--output" => output = Some(PathBuf::from(value(&arg)?)),
+            "--version" => {
+                let text = value(&arg)?;
+                version = text
+                    .parse()
+                    .map_err(|_| format!("`{}` is not a GLSL version", text))?;
+            }
+            "--system" => {
+                let name = value(&arg)?;
+                system =
+                    System::from_name(&name).ok_or_else(|| format!("unknown system `{}`", name))?;
+            }
+            "-D" => defines.push(define(&value(&arg)?)?),
+            "-f" | "--force" => force = true,
+            "-q" | "--quiet" => quiet = true,
+            _ => {
+                if let Some(definition) = arg.strip_prefix("-D") {
+                    defines.push(define(definition)?);
+                } else if arg.starts_with('-') {
+                    return Err(format!("unknown option `{}`", arg));
+                } else {
+                    inputs.push(PathBuf::from(arg));
+                }
+            }
+        }
+    }
+
+    if targets.is_empty() {
+        return Err("no target given, use `--target <TARGET>`".to_owned());
+    }
+    if inputs.is_empty() {
+        return Err("no input given".to_owned());
+    }
+    Ok(Some(Args {
+        inputs,
+        output: output.ok_or("no output directory given, use `--output <DIR>`")?,
+        targets,
+        defines,
+        version,
+        system,
+        force,
+        quiet,
+    }))
+}
+
+/// `NAME=VALUE`, or `NAME` which is defined as 1 like in C compilers.
+fn define(definition: &str) -> Result<(String, String), String> {
+    let (name, value) = definition.split_once('=').unwrap_or((definition, "1"));
+    if name.is_empty() || name.contains(char::is_whitespace) {
+        return Err(format!("`{}` is not a valid define", definition));
+    }
+    Ok((name.to_owned(), value.to_owned()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_args(args: &[&str]) -> Result<Option<Args>, String> {
+        parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn parses_options_and_inputs() {
+        let args = parse_args(&[
+            "-t",
+            "spirv, essl",
+            "--target",
+            "spirv",
+            "-o",
+            "out",
+            "-DLIGHTS=4",
+            "-D",
+            "SHADOWS",
+            "--version",
+            "450",
+            "--system",
+            "android",
+            "-f",
+            "shaders",
+            "extra.frag",
+        ])
+        .unwrap()
+        .unwrap();
+        assert_eq!(args.targets, [Target::Spirv, Target::Essl]);
+        assert_eq!(args.output, PathBuf::from("out"));
+        assert_eq!(
+            args.defines,
+            [
+                ("LIGHTS".to_owned(), "4".to_owned()),
+                ("SHADOWS".to_owned(), "1".to_owned())
+            ]
+        );
+        assert_eq!(args.version, 450);
+        assert_eq!(args.system, System::Android);
+        assert!(args.force);
+        assert!(!args.quiet);
+        assert_eq!(
+            args.inputs,
+            [PathBuf::from("shaders"), PathBuf::from("extra.frag")]
+        );
+    }
+
+    #[test]
+    fn defaults_to_the_host_and_version_300() {
+        let args = parse_args(&["-q", "-t", "glsl", "-o", "out", "shaders"])
+            .unwrap()
+            .unwrap();
+        assert_eq!(args.version, 300);
+        assert_eq!(args.system, System::host());
+        assert!(args.defines.is_empty());
+        assert!(args.quiet);
+        assert!(!args.force);
+    }
+
+    #[test]
+    fn asks_for_help() {
+        assert!(parse_args(&["-t", "glsl", "--help"]).unwrap().is_none());
+        assert!(parse_args(&["-h"]).unwrap().is_none());
+    }
+
+    #[test]
+    fn rejects_bad_command_lines() {
+        for (args, error) in [
+            (
+                &["-o", "out", "a.vert"][..],
+                "no target given, use `--target <TARGET>`",
+            ),
+            (&["-t", "glsl", "-o", "out"], "no input given"),
+            (
+                &["-t", "glsl", "a.vert"],
+                "no output directory given, use `--output <DIR>`",
+            ),
+            (&["-t", "vulkan"], "unknown target `vulkan`"),
+            (&["-t"], "`-t` expects a value"),
+            (&["--version", "new"], "`new` is not a GLSL version"),
+            (&["--system", "beos"], "unknown system `beos`"),
+            (&["-D", "=1"], "`=1` is not a valid define"),
+            (&["-DA B"], "`A B` is not a valid define"),
+            (&["--verbose"], "unknown option `--verbose`"),
+        ] {
+            assert_eq!(parse_args(args).err().as_deref(), Some(error), "{:?}", args);
+        }
+    }
+}
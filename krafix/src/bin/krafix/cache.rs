@@ -0,0 +1,182 @@
+use std::{
+    collections::BTreeMap,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+/// The name of the manifest in the output directory.
+const MANIFEST: &str = ".krafix-cache";
+
+/// The content hash of every output file, as of the run that wrote it.
+///
+/// The manifest has one `<hash> <path>` line per output, with the path relative to the output directory.
+pub struct Cache {
+    path: PathBuf,
+    entries: BTreeMap<String, u64>,
+}
+
+impl Cache {
+    /// Loads the manifest from `output`, a missing or unreadable manifest makes every shader out of date.
+    pub fn load(output: &Path) -> Self {
+        let path = output.join(MANIFEST);
+        let entries = fs::read_to_string(&path)
+            .map(|manifest| {
+                manifest
+                    .lines()
+                    .filter_map(|line| {
+                        let (hash, file) = line.split_once(' ')?;
+                        Some((file.to_owned(), u64::from_str_radix(hash, 16).ok()?))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        Self { path, entries }
+    }
+
+    /// Whether `file` was written from inputs with this `hash` and still exists.
+    pub fn is_fresh(&self, output: &Path, file: &str, hash: u64) -> bool {
+        self.entries.get(file) == Some(&hash) && output.join(file).is_file()
+    }
+
+    pub fn insert(&mut self, file: String, hash: u64) {
+        self.entries.insert(file, hash);
+    }
+
+    pub fn remove(&mut self, file: &str) {
+        self.entries.remove(file);
+    }
+
+    /// The outputs in the manifest, in the order of their paths.
+    pub fn files(&self) -> impl Iterator<Item = &str> {
+        self.entries.keys().map(String::as_str)
+    }
+
+    /// Writes the manifest to a temporary file first, so an interrupted run leaves the old one intact.
+    pub fn save(&self) -> io::Result<()> {
+        let mut manifest = String::new();
+        for (file, hash) in &self.entries {
+            manifest.push_str(&format!("{:016x} {}\n", hash, file));
+        }
+        let temporary = self.path.with_extension("tmp");
+        fs::write(&temporary, manifest)?;
+        fs::rename(&temporary, &self.path)
+    }
+}
+
+/// 64-bit FNV-1a, which is stable across Rust versions unlike `DefaultHasher`.
+#[derive(Clone)]
+pub struct Fnv(u64);
+
+impl Fnv {
+    pub fn new() -> Self {
+        Fnv(0xcbf2_9ce4_8422_2325)
+    }
+
+    pub fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= u64::from(*byte);
+            self.0 = self.0.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+
+    /// Writes `field` followed by a separator, so that consecutive fields can't run into each other.
+    pub fn field(&mut self, field: &str) {
+        self.write(field.as_bytes());
+        self.write(&[0]);
+    }
+
+    pub fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, process};
+
+    use super::*;
+
+    fn directory(name: &str) -> PathBuf {
+        let directory = env::temp_dir().join(format!("krafix-cache-{}-{}", process::id(), name));
+        let _ = fs::remove_dir_all(&directory);
+        fs::create_dir_all(&directory).unwrap();
+        directory
+    }
+
+    #[test]
+    fn round_trips_through_the_manifest() {
+        let output = directory("round-trip");
+        let mut cache = Cache::load(&output);
+        assert_eq!(cache.files().count(), 0);
+        cache.insert("sub/a.vert.spirv".to_owned(), 0x1234);
+        cache.insert("b.frag.json".to_owned(), u64::MAX);
+        cache.insert("gone.frag.json".to_owned(), 1);
+        cache.remove("gone.frag.json");
+        cache.save().unwrap();
+
+        assert_eq!(
+            fs::read_to_string(output.join(MANIFEST)).unwrap(),
+            "ffffffffffffffff b.frag.json\n0000000000001234 sub/a.vert.spirv\n"
+        );
+        let cache = Cache::load(&output);
+        assert_eq!(
+            cache.files().collect::<Vec<_>>(),
+            ["b.frag.json", "sub/a.vert.spirv"]
+        );
+        assert!(!output.join("krafix-cache.tmp").exists());
+    }
+
+    #[test]
+    fn is_fresh_with_the_same_hash_and_an_existing_file() {
+        let output = directory("fresh");
+        fs::write(output.join("a.vert.spirv"), b"").unwrap();
+        let mut cache = Cache::load(&output);
+        cache.insert("a.vert.spirv".to_owned(), 7);
+        cache.insert("missing.vert.spirv".to_owned(), 7);
+
+        assert!(cache.is_fresh(&output, "a.vert.spirv", 7));
+        assert!(!cache.is_fresh(&output, "a.vert.spirv", 8));
+        assert!(!cache.is_fresh(&output, "missing.vert.spirv", 7));
+        assert!(!cache.is_fresh(&output, "other.vert.spirv", 7));
+    }
+
+    #[test]
+    fn skips_broken_manifest_lines() {
+        let output = directory("broken");
+        fs::write(
+            output.join(MANIFEST),
+            "000000000000000a a.vert.spirv\nnot a hash\n\nzz b.vert.spirv\n0b c d.frag.json\n",
+        )
+        .unwrap();
+        let cache = Cache::load(&output);
+        assert_eq!(
+            cache.files().collect::<Vec<_>>(),
+            ["a.vert.spirv", "c d.frag.json"]
+        );
+    }
+
+    #[test]
+    fn fnv_matches_the_reference() {
+        let hash = |bytes: &[u8]| {
+            let mut fnv = Fnv::new();
+            fnv.write(bytes);
+            fnv.finish()
+        };
+        assert_eq!(hash(b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(hash(b"a"), 0xaf63_dc4c_8601_ec8c);
+        assert_eq!(hash(b"foobar"), 0x8594_4171_f739_67e8);
+    }
+
+    #[test]
+    fn fields_do_not_run_into_each_other() {
+        let fields = |fields: &[&str]| {
+            let mut fnv = Fnv::new();
+            for field in fields {
+                fnv.field(field);
+            }
+            fnv.finish()
+        };
+        assert_ne!(fields(&["ab", "c"]), fields(&["a", "bc"]));
+        assert_eq!(fields(&["ab", "c"]), fields(&["ab", "c"]));
+    }
+}
@@ -0,0 +1,172 @@
+use std::fmt::Write;
+
+use krafix::{reflect::Reflection, ShaderKind};
+
+/// The reflection data of a shader as a JSON object like
+/// `{"kind": "vertex", "attributes": [{"name": "pos", "location": 0, "type": "vec3"}], ...}`.
+pub fn reflection(kind: ShaderKind, reflection: &Reflection) -> String {
+    let mut json = String::new();
+    json.push_str("{\n");
+    writeln!(json, "  \"kind\": {},", string(kind.name())).unwrap();
+
+    let attributes = reflection.attributes.iter().map(|attribute| {
+        format!(
+            "{{\"name\": {}, \"location\": {}, \"type\": {}}}",
+            string(&attribute.name),
+            attribute.location,
+            string(attribute.ty.name())
+        )
+    });
+    array(&mut json, "attributes", attributes);
+
+    let uniforms = reflection.uniforms.iter().map(|uniform| {
+        format!(
+            "{{\"name\": {}, \"type\": {}, \"count\": {}}}",
+            string(&uniform.name),
+            string(uniform.ty.name()),
+            uniform.count
+        )
+    });
+    array(&mut json, "uniforms", uniforms);
+
+    let samplers = reflection.samplers.iter().map(|sampler| {
+        format!(
+            "{{\"name\": {}, \"binding\": {}, \"set\": {}, \"type\": {}}}",
+            string(&sampler.name),
+            optional(sampler.binding),
+            optional(sampler.set),
+            string(sampler.ty.name())
+        )
+    });
+    array(&mut json, "samplers", samplers);
+
+    match reflection.workgroup_size {
+        Some([x, y, z]) => writeln!(json, "  \"workgroup_size\": [{}, {}, {}]", x, y, z).unwrap(),
+        None => json.push_str("  \"workgroup_size\": null\n"),
+    }
+    json.push_str("}\n");
+    json
+}
+
+fn array(json: &mut String, name: &str, elements: impl Iterator<Item = String>) {
+    let elements: Vec<String> = elements.map(|element| format!("    {}", element)).collect();
+    if elements.is_empty() {
+        writeln!(json, "  {}: [],", string(name)).unwrap();
+    } else {
+        writeln!(
+            json,
+            "  {}: [\n{}\n  ],",
+            string(name),
+            elements.join(",\n")
+        )
+        .unwrap();
+    }
+}
+
+fn optional(value: Option<u32>) -> String {
+    match value {
+        Some(value) => value.to_string(),
+        None => "null".to_owned(),
+    }
+}
+
+fn string(text: &str) -> String {
+    let mut json = String::with_capacity(text.len() + 2);
+    json.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if u32::from(c) < 0x20 => write!(json, "\\u{:04x}", u32::from(c)).unwrap(),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
+
+#[cfg(test)]
+mod tests {
+    use krafix::reflect::{Attribute, DataType, Sampler, Uniform};
+
+    use super::*;
+
+    #[test]
+    fn writes_every_section() {
+        let data = Reflection {
+            attributes: vec![Attribute {
+                name: "pos".to_owned(),
+                location: 0,
+                ty: DataType::Vec3,
+            }],
+            uniforms: vec![
+                Uniform {
+                    name: "mvp".to_owned(),
+                    ty: DataType::Mat4,
+                    count: 1,
+                },
+                Uniform {
+                    name: "lights".to_owned(),
+                    ty: DataType::Vec4,
+                    count: 4,
+                },
+            ],
+            samplers: vec![Sampler {
+                name: "albedo".to_owned(),
+                binding: Some(1),
+                set: None,
+                ty: DataType::Sampler2D,
+            }],
+            workgroup_size: None,
+        };
+        assert_eq!(
+            reflection(ShaderKind::Vertex, &data),
+            r#"{
+  "kind": "vertex",
+  "attributes": [
+    {"name": "pos", "location": 0, "type": "vec3"}
+  ],
+  "uniforms": [
+    {"name": "mvp", "type": "mat4", "count": 1},
+    {"name": "lights", "type": "vec4", "count": 4}
+  ],
+  "samplers": [
+    {"name": "albedo", "binding": 1, "set": null, "type": "sampler2D"}
+  ],
+  "workgroup_size": null
+}
+"#
+        );
+    }
+
+    #[test]
+    fn writes_empty_sections_and_the_workgroup_size() {
+        let data = Reflection {
+            workgroup_size: Some([8, 4, 1]),
+            ..Reflection::default()
+        };
+        assert_eq!(
+            reflection(ShaderKind::Compute, &data),
+            r#"{
+  "kind": "compute",
+  "attributes": [],
+  "uniforms": [],
+  "samplers": [],
+  "workgroup_size": [8, 4, 1]
+}
+"#
+        );
+    }
+
+    #[test]
+    fn escapes_strings() {
+        assert_eq!(string("plain"), r#""plain""#);
+        assert_eq!(
+            string("a\"b\\c\nd\re\tf\u{1}g\u{7f}ü"),
+            "\"a\\\"b\\\\c\\nd\\re\\tf\\u0001g\u{7f}ü\""
+        );
+    }
+}
@@ -0,0 +1,441 @@
+//! Compiles shader directories outside of cargo builds, for asset pipelines that bake shaders ahead of time.
+//!
+//! `krafix -t spirv -t essl -o build/shaders shaders` writes `build/shaders/sub/name.vert.spirv`,
+//! `build/shaders/sub/name.vert.essl` and `build/shaders/sub/name.vert.json` for `shaders/sub/name.vert`.
+
+use std::{
+    collections::BTreeSet,
+    fs, io,
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+use krafix::{include, CompileError, Options, ShaderKind, Target};
+
+mod args;
+mod cache;
+mod json;
+
+use args::Args;
+use cache::{Cache, Fnv};
+
+struct Shader {
+    path: PathBuf,
+    /// The path relative to the input directory with `/` separators, which is also used for the outputs.
+    name: String,
+    kind: ShaderKind,
+}
+
+#[derive(Default)]
+struct Summary {
+    compiled: usize,
+    fresh: usize,
+    removed: usize,
+    failed: usize,
+}
+
+fn main() -> ExitCode {
+    let args = match args::parse(std::env::args().skip(1)) {
+        Ok(Some(args)) => args,
+        Ok(None) => {
+            print!("{}", args::USAGE);
+            return ExitCode::SUCCESS;
+        }
+        Err(error) => {
+            eprintln!("error: {}\n\nRun `krafix --help` for the usage.", error);
+            return ExitCode::from(2);
+        }
+    };
+
+    let shaders = match find_shaders(&args.inputs) {
+        Ok(shaders) => shaders,
+        Err(error) => {
+            eprintln!("error: {}", error);
+            return ExitCode::FAILURE;
+        }
+    };
+    if let Err(error) = fs::create_dir_all(&args.output) {
+        eprintln!(
+            "error: could not create `{}`: {}",
+            args.output.display(),
+            error
+        );
+        return ExitCode::FAILURE;
+    }
+
+    let mut cache = Cache::load(&args.output);
+    let mut summary = Summary::default();
+    for shader in &shaders {
+        bake(&args, shader, &mut cache, &mut summary);
+    }
+    prune(&args, &shaders, &mut cache, &mut summary);
+    if let Err(error) = cache.save() {
+        eprintln!("error: could not write the cache manifest: {}", error);
+        return ExitCode::FAILURE;
+    }
+
+    if !args.quiet {
+        println!(
+            "{} compiled, {} up to date, {} removed, {} failed",
+            summary.compiled, summary.fresh, summary.removed, summary.failed
+        );
+    }
+    if summary.failed > 0 {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+/// Writes the outputs of every target and the reflection JSON of one shader, skipping the fresh ones.
+fn bake(args: &Args, shader: &Shader, cache: &mut Cache, summary: &mut Summary) {
+    let expanded = match include::expand(&shader.path) {
+        Ok(expanded) => expanded,
+        Err(error) => {
+            eprintln!("error: {}", error);
+            summary.failed += 1;
+            return;
+        }
+    };
+
+    let mut hash = Fnv::new();
+    hash.field(env!("CARGO_PKG_VERSION"));
+    hash.field(shader.kind.name());
+    hash.field(&args.version.to_string());
+    hash.field(args.system.name());
+    for (name, value) in &args.defines {
+        hash.field(name);
+        hash.field(value);
+    }
+    hash.field(&expanded.source);
+
+    let options = |target: Target| {
+        let mut options = Options::new(shader.kind, target)
+            .version(args.version)
+            .system(args.system);
+        for (name, value) in &args.defines {
+            options = options.define(name, value);
+        }
+        options
+    };
+
+    for (target, extension) in outputs(args) {
+        let file = format!("{}.{}", shader.name, extension);
+        let mut output_hash = hash.clone();
+        output_hash.field(extension);
+        let output_hash = output_hash.finish();
+        if !args.force && cache.is_fresh(&args.output, &file, output_hash) {
+            summary.fresh += 1;
+            continue;
+        }
+
+        let output = match target {
+            Some(target) => options(target).compile(&expanded.source),
+            None => options(args.targets[0])
+                .reflect(&expanded.source)
+                .map(|reflection| json::reflection(shader.kind, &reflection).into_bytes()),
+        };
+        let result = match output {
+            Ok(output) => write(&args.output.join(&file), &output),
+            Err(error) => Err(describe(&error, &shader.path, &expanded)),
+        };
+        match result {
+            Ok(()) => {
+                if !args.quiet {
+                    println!("{}", file);
+                }
+                cache.insert(file, output_hash);
+                summary.compiled += 1;
+            }
+            Err(error) => {
+                eprint!("{}", error);
+                cache.remove(&file);
+                summary.failed += 1;
+            }
+        }
+    }
+}
+
+/// The target and file extension of every output of a shader,
+/// the reflection is the same for every target and `None` stands for the JSON file.
+fn outputs(args: &Args) -> impl Iterator<Item = (Option<Target>, &'static str)> + '_ {
+    args.targets
+        .iter()
+        .map(|target| (Some(*target), target.name()))
+        .chain([(None, "json")])
+}
+
+/// Deletes the outputs in the manifest that no shader produces anymore,
+/// because the shader was deleted or renamed, or its target isn't given anymore.
+fn prune(args: &Args, shaders: &[Shader], cache: &mut Cache, summary: &mut Summary) {
+    let current: BTreeSet<String> = shaders
+        .iter()
+        .flat_map(|shader| {
+            outputs(args).map(move |(_, extension)| format!("{}.{}", shader.name, extension))
+        })
+        .collect();
+    for file in cache
+        .files()
+        .filter(|file| !current.contains(*file))
+        .map(str::to_owned)
+        .collect::<Vec<_>>()
+    {
+        match fs::remove_file(args.output.join(&file)) {
+            Ok(()) => {
+                if !args.quiet {
+                    println!("removed {}", file);
+                }
+                summary.removed += 1;
+            }
+            Err(error) if error.kind() == io::ErrorKind::NotFound => {}
+            Err(error) => {
+                // Stays in the manifest, so the next run tries again
+                eprintln!("error: could not remove `{}`: {}", file, error);
+                summary.failed += 1;
+                continue;
+            }
+        }
+        cache.remove(&file);
+    }
+}
+
+fn write(path: &Path, output: &[u8]) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|error| {
+            format!(
+                "error: could not create `{}`: {}\n",
+                parent.display(),
+                error
+            )
+        })?;
+    }
+    fs::write(path, output)
+        .map_err(|error| format!("error: could not write `{}`: {}\n", path.display(), error))
+}
+
+/// One `error: file:line: message` line per compiler message, with the lines mapped back through includes.
+fn describe(error: &CompileError, path: &Path, expanded: &include::Expanded) -> String {
+    let mut description = String::new();
+    for message in error.messages() {
+        let location = match message.line.and_then(|line| expanded.origin(line)) {
+            Some((file, line)) => format!("{}:{}", file.display(), line),
+            None => path.display().to_string(),
+        };
+        description.push_str(&format!("error: {}: {}\n", location, message.text));
+    }
+    if description.is_empty() {
+        description = format!("error: {}: {}\n", path.display(), error.log().trim());
+    }
+    description
+}
+
+/// The shaders in `inputs`, where directories are searched recursively.
+fn find_shaders(inputs: &[PathBuf]) -> Result<Vec<Shader>, String> {
+    let mut shaders = Vec::new();
+    for input in inputs {
+        if input.is_dir() {
+            find_in_directory(input, input, &mut shaders)?;
+        } else {
+            let kind = shader_kind(input).ok_or_else(|| {
+                format!(
+                    "`{}` is not a directory or a file with a shader extension",
+                    input.display()
+                )
+            })?;
+            let name = input
+                .file_name()
+                .ok_or_else(|| format!("`{}` is not a file", input.display()))?;
+            shaders.push(Shader {
+                path: input.clone(),
+                name: name.to_string_lossy().into_owned(),
+                kind,
+            });
+        }
+    }
+    shaders.sort_by(|a, b| a.name.cmp(&b.name));
+    // The same file given twice, like a directory and a file in it
+    shaders.dedup_by(|a, b| a.name == b.name && same_file(&a.path, &b.path));
+    if let Some(pair) = shaders.windows(2).find(|pair| pair[0].name == pair[1].name) {
+        return Err(format!(
+            "`{}` and `{}` would both be compiled to `{}`",
+            pair[0].path.display(),
+            pair[1].path.display(),
+            pair[0].name
+        ));
+    }
+    Ok(shaders)
+}
+
+fn find_in_directory(
+    root: &Path,
+    directory: &Path,
+    shaders: &mut Vec<Shader>,
+) -> Result<(), String> {
+    let entries = fs::read_dir(directory)
+        .map_err(|error| format!("could not read `{}`: {}", directory.display(), error))?;
+    for entry in entries {
+        let path = entry
+            .map_err(|error| format!("could not read `{}`: {}", directory.display(), error))?
+            .path();
+        if path.is_dir() {
+            find_in_directory(root, &path, shaders)?;
+        } else if let Some(kind) = shader_kind(&path) {
+            let name = path
+                .strip_prefix(root)
+                .unwrap_or(&path)
+                .components()
+                .map(|component| component.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            shaders.push(Shader { path, name, kind });
+        }
+    }
+    Ok(())
+}
+
+fn same_file(a: &Path, b: &Path) -> bool {
+    match (fs::canonicalize(a), fs::canonicalize(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
+
+fn shader_kind(path: &Path) -> Option<ShaderKind> {
+    ShaderKind::from_extension(path.extension()?.to_str()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, process};
+
+    use krafix::System;
+
+    use super::*;
+
+    /// A fresh directory with the given files.
+    fn directory(name: &str, files: &[&str]) -> PathBuf {
+        let directory = env::temp_dir().join(format!("krafix-main-{}-{}", process::id(), name));
+        let _ = fs::remove_dir_all(&directory);
+        for file in files {
+            let path = directory.join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, "").unwrap();
+        }
+        directory
+    }
+
+    fn names(shaders: &[Shader]) -> Vec<&str> {
+        shaders.iter().map(|shader| shader.name.as_str()).collect()
+    }
+
+    #[test]
+    fn finds_shaders_by_extension() {
+        let shaders = directory(
+            "find",
+            &[
+                "b.frag",
+                "sub/a.vert",
+                "sub/deeper/c.comp",
+                "readme.md",
+                "common.glsl",
+            ],
+        );
+        let found = find_shaders(&[shaders.clone(), shaders.join("b.frag")]).unwrap();
+        assert_eq!(names(&found), ["b.frag", "sub/a.vert", "sub/deeper/c.comp"]);
+        let kinds: Vec<_> = found.iter().map(|shader| shader.kind).collect();
+        assert_eq!(
+            kinds,
+            [
+                ShaderKind::Fragment,
+                ShaderKind::Vertex,
+                ShaderKind::Compute
+            ]
+        );
+
+        assert!(find_shaders(&[shaders.join("readme.md")]).is_err());
+    }
+
+    #[test]
+    fn rejects_shaders_with_the_same_output() {
+        let shaders = directory("duplicates", &["one/x.vert", "two/x.vert", "two/y.vert"]);
+        let (one, two) = (shaders.join("one"), shaders.join("two"));
+        let error = find_shaders(&[one.clone(), two.clone()]).err().unwrap();
+        assert_eq!(
+            error,
+            format!(
+                "`{}` and `{}` would both be compiled to `x.vert`",
+                one.join("x.vert").display(),
+                two.join("x.vert").display()
+            )
+        );
+
+        let error = find_shaders(&[two.join("y.vert"), one.join("x.vert"), two.join("x.vert")])
+            .err()
+            .unwrap();
+        assert!(
+            error.ends_with("would both be compiled to `x.vert`"),
+            "{}",
+            error
+        );
+    }
+
+    #[test]
+    fn prunes_outputs_of_removed_shaders_and_targets() {
+        let output = directory(
+            "prune",
+            &[
+                "a.vert.spirv",
+                "a.vert.essl",
+                "a.vert.json",
+                "sub/old.frag.spirv",
+                "sub/old.frag.json",
+                "unrelated.txt",
+            ],
+        );
+        let mut cache = Cache::load(&output);
+        for file in [
+            "a.vert.spirv",
+            "a.vert.essl",
+            "a.vert.json",
+            "sub/old.frag.spirv",
+            "sub/old.frag.json",
+            "deleted.frag.json",
+        ] {
+            cache.insert(file.to_owned(), 1);
+        }
+        let args = Args {
+            inputs: Vec::new(),
+            output: output.clone(),
+            targets: vec![Target::Spirv],
+            defines: Vec::new(),
+            version: 300,
+            system: System::host(),
+            force: false,
+            quiet: true,
+        };
+        let shaders = [Shader {
+            path: PathBuf::from("a.vert"),
+            name: "a.vert".to_owned(),
+            kind: ShaderKind::Vertex,
+        }];
+        let mut summary = Summary::default();
+        prune(&args, &shaders, &mut cache, &mut summary);
+
+        assert_eq!(summary.removed, 3);
+        assert_eq!(summary.failed, 0);
+        assert_eq!(
+            cache.files().collect::<Vec<_>>(),
+            ["a.vert.json", "a.vert.spirv"]
+        );
+        for (file, exists) in [
+            ("a.vert.spirv", true),
+            ("a.vert.json", true),
+            ("unrelated.txt", true),
+            ("a.vert.essl", false),
+            ("sub/old.frag.spirv", false),
+            ("sub/old.frag.json", false),
+        ] {
+            assert_eq!(output.join(file).exists(), exists, "{}", file);
+        }
+    }
+}
@@ -0,0 +1,128 @@
+use std::ffi::c_void;
+
+/// Receives a chunk of data, see `krafix_rust_write` in shim/shim.h.
+pub(crate) type Write = extern "C" fn(context: *mut c_void, data: *const u8, length: usize);
+
+extern "C" {
+    fn krafix_rust_log_begin(write: Write, context: *mut c_void);
+    fn krafix_rust_log_end();
+}
+
+/// A [`Write`] callback that appends to the `Vec<u8>` passed as the context.
+pub(crate) extern "C" fn append(context: *mut c_void, data: *const u8, length: usize) {
+    // Safety: the context is the Vec passed with the callback, and data is valid for length bytes
+    unsafe {
+        let buffer = &mut *context.cast::<Vec<u8>>();
+        buffer.extend_from_slice(std::slice::from_raw_parts(data, length));
+    }
+}
+
+/// Stops capturing when dropped, even if the captured function panics.
+struct Capture;
+
+impl Drop for Capture {
+    fn drop(&mut self) {
+        // Safety: only resets the sink of the current thread
+        unsafe { krafix_rust_log_end() }
+    }
+}
+
+/// Runs `f` while everything krafix and glslang print to stdout and stderr on this thread is collected,
+/// and returns it next to the result.
+///
+/// glslang reports errors by printing them. shim/log.h is force-included into its sources and
+/// redirects the printing to a per-thread sink, so the process' output stays untouched.
+pub(crate) fn capture_output<R>(f: impl FnOnce() -> R) -> (R, String) {
+    let mut log = Vec::new();
+    let result = {
+        // Safety: the sink is reset before `log` goes out of scope
+        unsafe { krafix_rust_log_begin(append, (&mut log as *mut Vec<u8>).cast()) };
+        let _capture = Capture;
+        f()
+    };
+    (result, String::from_utf8_lossy(&log).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        ffi::{c_char, c_int, CString},
+        panic,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Barrier,
+        },
+        thread,
+    };
+
+    use super::*;
+
+    // The redirected functions from shim/log.cpp, which glslang calls instead of printf and puts
+    extern "C" {
+        fn krafix_rust_printf(format: *const c_char, ...) -> c_int;
+        fn krafix_rust_puts(text: *const c_char) -> c_int;
+        fn krafix_rust_putchar(c: c_int) -> c_int;
+    }
+
+    #[test]
+    fn collects_printed_text() {
+        let ((), log) = capture_output(|| unsafe {
+            krafix_rust_printf(
+                c"ERROR: 0:%d: '%s' : undeclared identifier\n".as_ptr(),
+                12,
+                c"x".as_ptr(),
+            );
+            krafix_rust_puts(c"line".as_ptr());
+            krafix_rust_putchar(c_int::from(b'!'));
+        });
+        assert_eq!(log, "ERROR: 0:12: 'x' : undeclared identifier\nline\n!");
+    }
+
+    #[test]
+    fn captures_are_per_thread() {
+        let barrier = Barrier::new(2);
+        let logs: Vec<String> = thread::scope(|scope| {
+            let threads: Vec<_> = ["first", "second"]
+                .into_iter()
+                .map(|name| {
+                    let barrier = &barrier;
+                    scope.spawn(move || {
+                        let text = CString::new(name).unwrap();
+                        capture_output(|| {
+                            // Both threads are capturing while they print
+                            barrier.wait();
+                            unsafe { krafix_rust_puts(text.as_ptr()) };
+                            barrier.wait();
+                        })
+                        .1
+                    })
+                })
+                .collect();
+            threads
+                .into_iter()
+                .map(|thread| thread.join().unwrap())
+                .collect()
+        });
+        assert_eq!(logs, ["first\n", "second\n"]);
+    }
+
+    static WRITES: AtomicUsize = AtomicUsize::new(0);
+
+    extern "C" fn count(_context: *mut c_void, _data: *const u8, _length: usize) {
+        WRITES.fetch_add(1, Ordering::SeqCst);
+    }
+
+    #[test]
+    fn stops_capturing_after_a_panic() {
+        let result = panic::catch_unwind(|| {
+            unsafe { krafix_rust_log_begin(count, std::ptr::null_mut()) };
+            let _capture = Capture;
+            panic!("compiler crashed");
+        });
+        assert!(result.is_err());
+
+        // Goes to the real stdout instead of the callback
+        unsafe { krafix_rust_puts(c"".as_ptr()) };
+        assert_eq!(WRITES.load(Ordering::SeqCst), 0);
+    }
+}
@@ -0,0 +1,54 @@
+/// A shader source with `#define` lines inserted after its `#version` directive,
+/// or at the top if it has none.
+pub(crate) struct Injected {
+    pub source: String,
+    /// The number of lines before the defines.
+    after: usize,
+    count: usize,
+}
+
+impl Injected {
+    pub fn new(source: &str, defines: &[(String, String)]) -> Self {
+        if defines.is_empty() {
+            return Injected {
+                source: source.to_owned(),
+                after: 0,
+                count: 0,
+            };
+        }
+        let after = source
+            .lines()
+            .position(|line| line.trim_start().starts_with("#version"))
+            .map_or(0, |line| line + 1);
+
+        let mut injected = String::with_capacity(source.len());
+        for line in source.lines().take(after) {
+            injected.push_str(line);
+            injected.push('\n');
+        }
+        for (name, value) in defines {
+            injected.push_str(&format!("#define {} {}\n", name, value));
+        }
+        for line in source.lines().skip(after) {
+            injected.push_str(line);
+            injected.push('\n');
+        }
+        Injected {
+            source: injected,
+            after,
+            count: defines.len(),
+        }
+    }
+
+    /// Maps a 1-based line of the injected source back to the original source,
+    /// lines of the defines map to the line before them.
+    pub fn original_line(&self, line: usize) -> usize {
+        if line <= self.after {
+            line
+        } else if line <= self.after + self.count {
+            self.after.max(1)
+        } else {
+            line - self.count
+        }
+    }
+}
@@ -0,0 +1,95 @@
+use std::fmt;
+
+/// A message from the compiler's error log.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Message {
+    /// The 1-based line in the shader source, if the compiler reported one.
+    pub line: Option<usize>,
+    pub text: String,
+}
+
+/// Why a shader failed to compile.
+#[derive(Clone, Debug)]
+pub struct CompileError {
+    log: String,
+    messages: Vec<Message>,
+}
+
+impl CompileError {
+    /// An error that didn't come from the compiler log.
+    pub(crate) fn new(text: impl Into<String>) -> Self {
+        let text = text.into();
+        Self {
+            messages: vec![Message {
+                line: None,
+                text: text.clone(),
+            }],
+            log: text,
+        }
+    }
+
+    /// Extracts the errors from krafix' output, which contains lines like `ERROR: 0:12: 'x' : undeclared identifier`.
+    /// `original_line` maps the lines of the compiled source back to the source the caller passed in.
+    pub(crate) fn from_log(log: String, original_line: impl Fn(usize) -> usize) -> Self {
+        let mut messages: Vec<Message> = log
+            .lines()
+            .filter_map(|line| line.trim().strip_prefix("ERROR:"))
+            .map(|error| {
+                let mut parts = error.splitn(3, ':');
+                let _source = parts.next();
+                match (parts.next().map(|l| l.trim().parse()), parts.next()) {
+                    (Some(Ok(line)), Some(text)) => Message {
+                        line: Some(original_line(line)),
+                        text: text.trim().to_owned(),
+                    },
+                    _ => Message {
+                        line: None,
+                        text: error.trim().to_owned(),
+                    },
+                }
+            })
+            .collect();
+        // Drops summaries like "1 compilation errors" if there are errors with a location
+        if messages.iter().any(|message| message.line.is_some()) {
+            messages.retain(|message| message.line.is_some());
+        }
+        Self { log, messages }
+    }
+
+    /// Everything the compiler printed.
+    pub fn log(&self) -> &str {
+        &self.log
+    }
+
+    /// The errors found in the log, empty if there were none in a format krafix is known to use.
+    pub fn messages(&self) -> &[Message] {
+        &self.messages
+    }
+}
+
+impl fmt::Display for Message {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "line {}: {}", line, self.text),
+            None => f.write_str(&self.text),
+        }
+    }
+}
+
+impl fmt::Display for CompileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.messages.split_first() {
+            Some((first, rest)) => {
+                write!(f, "{}", first)?;
+                for message in rest {
+                    write!(f, "\n{}", message)?;
+                }
+                Ok(())
+            }
+            None if self.log.trim().is_empty() => f.write_str("failed to compile shader"),
+            None => write!(f, "failed to compile shader:\n{}", self.log.trim()),
+        }
+    }
+}
+
+impl std::error::Error for CompileError {}
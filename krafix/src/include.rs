@@ -0,0 +1,227 @@
+//! `#include "file"` resolution for shaders read from disk.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// A shader with all `#include`s replaced by the contents of the included files.
+pub struct Expanded {
+    pub source: String,
+    /// Every file that went into the source, for rebuilding when one of them changes.
+    pub files: Vec<PathBuf>,
+    /// The file index and 1-based line in that file for every line of `source`.
+    lines: Vec<(usize, usize)>,
+}
+
+impl Expanded {
+    /// Where a 1-based line of the expanded source came from.
+    pub fn origin(&self, line: usize) -> Option<(&Path, usize)> {
+        let (file, line) = *self.lines.get(line.checked_sub(1)?)?;
+        Some((&self.files[file], line))
+    }
+}
+
+/// Reads the shader at `path` and expands its `#include "file"` directives,
+/// which are resolved relative to the file containing them.
+pub fn expand(path: &Path) -> Result<Expanded, String> {
+    let mut expanded = Expanded {
+        source: String::new(),
+        files: Vec::new(),
+        lines: Vec::new(),
+    };
+    expand_into(&mut expanded, path, &mut Vec::new())?;
+    Ok(expanded)
+}
+
+fn expand_into(
+    expanded: &mut Expanded,
+    path: &Path,
+    stack: &mut Vec<PathBuf>,
+) -> Result<(), String> {
+    let path = fs::canonicalize(path)
+        .map_err(|error| format!("could not open `{}`: {}", path.display(), error))?;
+    if stack.contains(&path) {
+        return Err(format!("`{}` includes itself", path.display()));
+    }
+    let source = fs::read_to_string(&path)
+        .map_err(|error| format!("could not read `{}`: {}", path.display(), error))?;
+
+    let file = match expanded.files.iter().position(|file| *file == path) {
+        Some(file) => file,
+        None => {
+            expanded.files.push(path.clone());
+            expanded.files.len() - 1
+        }
+    };
+    // Errors name `path` rather than the top of the stack, which early returns leave unpopped
+    stack.push(path.clone());
+    for (index, line) in source.lines().enumerate() {
+        match parse_include(line) {
+            Some(Ok(include)) => {
+                let include = path.parent().unwrap().join(include);
+                expand_into(expanded, &include, stack)
+                    .map_err(|error| format!("{}:{}: {}", path.display(), index + 1, error))?;
+            }
+            Some(Err(())) => {
+                return Err(format!(
+                    "{}:{}: expected `#include \"file\"`",
+                    path.display(),
+                    index + 1
+                ))
+            }
+            None => {
+                expanded.source.push_str(line);
+                expanded.source.push('\n');
+                expanded.lines.push((file, index + 1));
+            }
+        }
+    }
+    stack.pop();
+    Ok(())
+}
+
+/// The path of an `#include "file"` line, `None` for other lines.
+fn parse_include(line: &str) -> Option<Result<&str, ()>> {
+    let directive = line.trim_start().strip_prefix('#')?.trim_start();
+    let argument = directive.strip_prefix("include")?.trim();
+    Some(
+        argument
+            .strip_prefix('"')
+            .and_then(|argument| argument.strip_suffix('"'))
+            .ok_or(()),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, process};
+
+    use super::*;
+
+    /// A fresh directory with the given `(path, contents)` files.
+    fn directory(name: &str, files: &[(&str, &str)]) -> PathBuf {
+        let directory = env::temp_dir().join(format!("krafix-include-{}-{}", process::id(), name));
+        let _ = fs::remove_dir_all(&directory);
+        for (path, contents) in files {
+            let path = directory.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, contents).unwrap();
+        }
+        fs::canonicalize(directory).unwrap()
+    }
+
+    #[test]
+    fn expands_nested_includes() {
+        let directory = directory(
+            "nested",
+            &[
+                (
+                    "shader.frag",
+                    "#version 450\n#include \"lib/light.glsl\"\nvoid main() {}\n",
+                ),
+                (
+                    "lib/light.glsl",
+                    "  # include \"math.glsl\"\nfloat light;\n",
+                ),
+                ("lib/math.glsl", "float pi;\n"),
+            ],
+        );
+        let expanded = expand(&directory.join("shader.frag")).unwrap();
+        assert_eq!(
+            expanded.source,
+            "#version 450\nfloat pi;\nfloat light;\nvoid main() {}\n"
+        );
+        assert_eq!(
+            expanded.files,
+            [
+                directory.join("shader.frag"),
+                directory.join("lib/light.glsl"),
+                directory.join("lib/math.glsl"),
+            ]
+        );
+    }
+
+    #[test]
+    fn maps_lines_to_their_origin() {
+        let directory = directory(
+            "origin",
+            &[
+                (
+                    "shader.vert",
+                    "#version 450\n#include \"common.glsl\"\nvoid main() {}\n#include \"common.glsl\"\n",
+                ),
+                ("common.glsl", "// common\nfloat x;\n"),
+            ],
+        );
+        let shader = directory.join("shader.vert");
+        let common = directory.join("common.glsl");
+        let expanded = expand(&shader).unwrap();
+
+        let origins: Vec<_> = (0..=7).map(|line| expanded.origin(line)).collect();
+        assert_eq!(
+            origins,
+            [
+                None,
+                Some((shader.as_path(), 1)),
+                Some((common.as_path(), 1)),
+                Some((common.as_path(), 2)),
+                Some((shader.as_path(), 3)),
+                Some((common.as_path(), 1)),
+                Some((common.as_path(), 2)),
+                None,
+            ]
+        );
+        // A file included twice is listed once
+        assert_eq!(expanded.files, [shader.clone(), common.clone()]);
+    }
+
+    #[test]
+    fn rejects_include_cycles() {
+        let directory = directory(
+            "cycle",
+            &[
+                ("shader.frag", "#include \"a.glsl\"\n"),
+                ("a.glsl", "float a;\n#include \"b.glsl\"\n"),
+                ("b.glsl", "#include \"a.glsl\"\n"),
+            ],
+        );
+        let error = expand(&directory.join("shader.frag")).err().unwrap();
+        let a = directory.join("a.glsl");
+        assert_eq!(
+            error,
+            format!(
+                "{}:1: {}:2: {}:1: `{}` includes itself",
+                directory.join("shader.frag").display(),
+                a.display(),
+                directory.join("b.glsl").display(),
+                a.display()
+            )
+        );
+    }
+
+    #[test]
+    fn reports_bad_includes_with_their_location() {
+        let directory = directory(
+            "bad",
+            &[
+                ("shader.frag", "float x;\n#include <light.glsl>\n"),
+                ("missing.frag", "#include \"light.glsl\"\n"),
+            ],
+        );
+        let shader = directory.join("shader.frag");
+        assert_eq!(
+            expand(&shader).err().unwrap(),
+            format!("{}:2: expected `#include \"file\"`", shader.display())
+        );
+
+        let missing = directory.join("missing.frag");
+        let error = expand(&missing).err().unwrap();
+        let prefix = format!(
+            "{}:1: could not open `{}`: ",
+            missing.display(),
+            directory.join("light.glsl").display()
+        );
+        assert!(error.starts_with(&prefix), "{}", error);
+    }
+}
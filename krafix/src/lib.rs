@@ -1,28 +1,35 @@
-use std::ffi::CString;
+//! Shader cross-compilation with [krafix](https://github.com/Kode/krafix).
+//!
+//! This crate runs the compiler at runtime, for tools, editors and hot reloading.
+//! `krafix-macros` uses it to compile shaders at build time.
+//!
+//! Compiling can be done from any thread, but compilations run one at a time. The compiler's messages are
+//! returned in [`CompileError`] instead of being printed, and the process' stdout and stderr are left alone.
 
-use proc_macro2::Span;
-use quote::quote;
-use syn::{
-    parse::{Parse, ParseStream, Result},
-    parse_macro_input, Ident, LitByteStr, LitStr, Token,
+use std::{
+    ffi::{c_void, CString},
+    sync::Mutex,
 };
 
-enum ShaderKind {
-    Vertex,
-    Fragment,
-}
+mod capture;
+mod defines;
+mod error;
+pub mod include;
+pub mod reflect;
 
-struct Shader {
-    kind: ShaderKind,
-    lang: String,
-    source: String,
-}
+pub use error::{CompileError, Message};
+
+use defines::Injected;
+
+/// glslang keeps global state, so only one compilation runs at a time.
+static COMPILER: Mutex<()> = Mutex::new(());
 
 extern "C" {
-    fn krafix_compile(
+    /// See shim/shim.h.
+    fn krafix_rust_compile(
         source: *const u8,
-        output: *mut u8,
-        length: *mut i32,
+        output: capture::Write,
+        output_context: *mut c_void,
         targetlang: *const u8,
         system: *const u8,
         shadertype: *const u8,
@@ -30,71 +37,381 @@ extern "C" {
     ) -> i32;
 }
 
-impl Parse for Shader {
-    fn parse(input: ParseStream) -> Result<Self> {
-        let kind = input.parse::<Ident>()?;
-        input.parse::<Token![,]>()?;
-        let lang = input.parse::<Ident>()?;
-        input.parse::<Token![,]>()?;
-        let source: LitStr = input.parse()?;
-        let shader_code = source.value();
-
-        let kind = match kind.to_string().as_str() {
-            "vertex" => ShaderKind::Vertex,
-            "fragment" => ShaderKind::Fragment,
-            _ => panic!("Unknown shader kind"),
-        };
-
-        Ok(Shader {
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ShaderKind {
+    Vertex,
+    Fragment,
+    Geometry,
+    TessControl,
+    TessEval,
+    Compute,
+}
+
+impl ShaderKind {
+    pub const ALL: [ShaderKind; 6] = [
+        ShaderKind::Vertex,
+        ShaderKind::Fragment,
+        ShaderKind::Geometry,
+        ShaderKind::TessControl,
+        ShaderKind::TessEval,
+        ShaderKind::Compute,
+    ];
+
+    /// The name used in `compile_shader!`, like `vertex` or `tess_control`.
+    pub fn name(self) -> &'static str {
+        match self {
+            ShaderKind::Vertex => "vertex",
+            ShaderKind::Fragment => "fragment",
+            ShaderKind::Geometry => "geometry",
+            ShaderKind::TessControl => "tess_control",
+            ShaderKind::TessEval => "tess_eval",
+            ShaderKind::Compute => "compute",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.name() == name)
+    }
+
+    /// The file extension krafix and Kinc use, like `vert` or `tesc`.
+    pub fn extension(self) -> &'static str {
+        match self {
+            ShaderKind::Vertex => "vert",
+            ShaderKind::Fragment => "frag",
+            ShaderKind::Geometry => "geom",
+            ShaderKind::TessControl => "tesc",
+            ShaderKind::TessEval => "tese",
+            ShaderKind::Compute => "comp",
+        }
+    }
+
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|kind| kind.extension() == extension)
+    }
+
+    fn krafix_name(self) -> &'static [u8] {
+        match self {
+            ShaderKind::Vertex => b"vert\0",
+            ShaderKind::Fragment => b"frag\0",
+            ShaderKind::Geometry => b"geom\0",
+            ShaderKind::TessControl => b"tesc\0",
+            ShaderKind::TessEval => b"tese\0",
+            ShaderKind::Compute => b"comp\0",
+        }
+    }
+}
+
+/// The language to compile to.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Target {
+    Glsl,
+    Essl,
+    Hlsl,
+    D3d9,
+    D3d11,
+    Metal,
+    Spirv,
+    Agal,
+    Varjo,
+    Js,
+}
+
+impl Target {
+    pub const ALL: [Target; 10] = [
+        Target::Glsl,
+        Target::Essl,
+        Target::Hlsl,
+        Target::D3d9,
+        Target::D3d11,
+        Target::Metal,
+        Target::Spirv,
+        Target::Agal,
+        Target::Varjo,
+        Target::Js,
+    ];
+
+    /// The name krafix uses, like `spirv` or `d3d11`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Target::Glsl => "glsl",
+            Target::Essl => "essl",
+            Target::Hlsl => "hlsl",
+            Target::D3d9 => "d3d9",
+            Target::D3d11 => "d3d11",
+            Target::Metal => "metal",
+            Target::Spirv => "spirv",
+            Target::Agal => "agal",
+            Target::Varjo => "varjo",
+            Target::Js => "js",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|target| target.name() == name)
+    }
+}
+
+/// The platform the shader is compiled for, some targets differ between platforms.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum System {
+    Windows,
+    WindowsApp,
+    MacOS,
+    Linux,
+    Android,
+    IOS,
+    TvOS,
+    Html5,
+}
+
+impl System {
+    pub const ALL: [System; 8] = [
+        System::Windows,
+        System::WindowsApp,
+        System::MacOS,
+        System::Linux,
+        System::Android,
+        System::IOS,
+        System::TvOS,
+        System::Html5,
+    ];
+
+    /// The system this crate is compiled for.
+    pub fn host() -> Self {
+        if cfg!(windows) {
+            System::Windows
+        } else if cfg!(target_os = "macos") {
+            System::MacOS
+        } else if cfg!(target_os = "android") {
+            System::Android
+        } else if cfg!(target_os = "ios") {
+            System::IOS
+        } else {
+            System::Linux
+        }
+    }
+
+    /// The name krafix uses, like `android` or `macos`.
+    pub fn name(self) -> &'static str {
+        match self {
+            System::Windows => "windows",
+            System::WindowsApp => "windowsapp",
+            System::MacOS => "macos",
+            System::Linux => "linux",
+            System::Android => "android",
+            System::IOS => "ios",
+            System::TvOS => "tvos",
+            System::Html5 => "html5",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|system| system.name() == name)
+    }
+}
+
+/// Everything besides the source that decides how a shader is compiled.
+#[derive(Clone, Debug)]
+pub struct Options {
+    kind: ShaderKind,
+    target: Target,
+    version: i32,
+    system: System,
+    defines: Vec<(String, String)>,
+}
+
+impl Options {
+    /// Options for version 300 on the host system.
+    pub fn new(kind: ShaderKind, target: Target) -> Self {
+        Self {
             kind,
-            lang: lang.to_string(),
-            source: shader_code,
-        })
+            target,
+            version: 300,
+            system: System::host(),
+            defines: Vec::new(),
+        }
     }
+
+    pub fn version(mut self, version: i32) -> Self {
+        self.version = version;
+        self
+    }
+
+    pub fn system(mut self, system: System) -> Self {
+        self.system = system;
+        self
+    }
+
+    /// Adds a `#define name value` after the `#version` directive.
+    pub fn define(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.defines.push((name.into(), value.into()));
+        self
+    }
+
+    pub fn kind(&self) -> ShaderKind {
+        self.kind
+    }
+
+    pub fn target(&self) -> Target {
+        self.target
+    }
+
+    /// Compiles GLSL `source`.
+    ///
+    /// Waits for compilations on other threads to finish first.
+    /// Line numbers in the returned error refer to `source`, not counting the inserted defines.
+    pub fn compile(&self, source: &str) -> Result<Vec<u8>, CompileError> {
+        let injected = Injected::new(source, &self.defines);
+        let source = CString::new(injected.source.as_str())
+            .map_err(|_| CompileError::new("shader source must not contain null bytes"))?;
+        let targetlang = CString::new(self.target.name()).unwrap();
+        let system = CString::new(self.system.name()).unwrap();
+        let shadertype = self.kind.krafix_name();
+
+        let _guard = COMPILER
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+        let mut output = Vec::new();
+        // Safety: the strings are null-terminated, and the output is only written during the call
+        let (result, log) = capture::capture_output(|| unsafe {
+            krafix_rust_compile(
+                source.as_ptr().cast(),
+                capture::append,
+                (&mut output as *mut Vec<u8>).cast(),
+                targetlang.as_ptr().cast(),
+                system.as_ptr().cast(),
+                shadertype.as_ptr(),
+                self.version,
+            )
+        });
+        if result != 0 {
+            return Err(CompileError::from_log(log, |line| {
+                injected.original_line(line)
+            }));
+        }
+        Ok(output)
+    }
+}
+
+impl Options {
+    /// Compiles `source` to SPIR-V, whatever the target is, and reads its inputs, uniforms and samplers.
+    pub fn reflect(&self, source: &str) -> Result<reflect::Reflection, CompileError> {
+        let spirv = Options {
+            target: Target::Spirv,
+            ..self.clone()
+        }
+        .compile(source)?;
+        reflect::reflect(&spirv).map_err(|error| CompileError::new(error.to_string()))
+    }
+}
+
+/// Compiles GLSL `source` for `target` on the host system.
+pub fn compile(
+    source: &str,
+    kind: ShaderKind,
+    target: Target,
+    version: i32,
+) -> Result<Vec<u8>, CompileError> {
+    Options::new(kind, target).version(version).compile(source)
 }
 
-#[proc_macro]
-pub fn compile_shader(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
-    let shader: Shader = parse_macro_input!(input as Shader);
-
-    let source = CString::new(shader.source).unwrap();
-    let targetlang = CString::new(shader.lang).unwrap();
-    let system = if cfg!(windows) {
-        &b"windows\0"[..]
-    } else if cfg!(target_os = "macos") {
-        &b"macos\0"[..]
-    } else {
-        &b"linux\0"[..]
-    };
-
-    let shadertype = match shader.kind {
-        ShaderKind::Vertex => b"vert\0",
-        ShaderKind::Fragment => b"frag\0",
-    };
-
-    let shaderversion = 300;
-
-    let mut v = vec![0_u8; 1024 * 1024];
-    let mut len: i32 = v.len() as i32;
-
-    unsafe {
-        if krafix_compile(
-            source.as_ptr().cast(),
-            v.as_mut_ptr(),
-            &mut len as *mut i32,
-            targetlang.as_ptr().cast(),
-            system.as_ptr(),
-            shadertype.as_ptr(),
-            shaderversion
-        ) != 0
-        {
-            panic!("Failed to compile shader");
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VERTEX: &str = "#version 450
+layout(location = 0) in vec3 pos;
+void main() {
+    gl_Position = vec4(pos, 1.0);
+}
+";
+
+    /// The number of words in each instruction of a SPIR-V module.
+    fn instruction_lengths(spirv: &[u8]) -> Vec<usize> {
+        let words: Vec<u32> = spirv
+            .chunks(4)
+            .map(|word| u32::from_le_bytes(word.try_into().unwrap()))
+            .collect();
+        let mut lengths = Vec::new();
+        let mut offset = 5;
+        while offset < words.len() {
+            let length = (words[offset] >> 16) as usize;
+            assert_ne!(length, 0, "instruction at word {}", offset);
+            lengths.push(length);
+            offset += length;
         }
+        assert_eq!(offset, words.len(), "the last instruction is cut off");
+        lengths
+    }
+
+    #[test]
+    fn compiles_a_complete_module() {
+        let spirv = Options::new(ShaderKind::Vertex, Target::Spirv)
+            .version(450)
+            .compile(VERTEX)
+            .unwrap();
+        assert_eq!(spirv.len() % 4, 0);
+        assert_eq!(spirv[..4], 0x0723_0203_u32.to_le_bytes());
+        assert!(!instruction_lengths(&spirv).is_empty());
     }
 
-    let byte_string = LitByteStr::new(&v[0..(len as usize)], Span::call_site());
-    quote! {
-        #byte_string
+    #[test]
+    fn reports_errors_at_their_original_line() {
+        let source = "#version 450
+layout(location = 0) out vec4 color;
+void main() {
+    color = vec4(undeclared);
+}
+";
+        let error = Options::new(ShaderKind::Fragment, Target::Spirv)
+            .version(450)
+            .define("FIRST", "1")
+            .define("SECOND", "2")
+            .compile(source)
+            .unwrap_err();
+        let message = &error.messages()[0];
+        assert_eq!(message.line, Some(4));
+        assert!(message.text.contains("undeclared"), "{}", message.text);
+        assert!(error.log().contains("undeclared"));
+    }
+
+    #[test]
+    fn compiles_on_several_threads() {
+        let results: Vec<_> = std::thread::scope(|scope| {
+            let threads: Vec<_> = (0..4)
+                .map(|index| {
+                    scope.spawn(move || {
+                        // Every other shader fails, and its error must not end up in another thread's result
+                        let source = if index % 2 == 0 {
+                            VERTEX.to_owned()
+                        } else {
+                            VERTEX.replace("pos, 1.0", &format!("missing{}, 1.0", index))
+                        };
+                        compile(&source, ShaderKind::Vertex, Target::Spirv, 450)
+                    })
+                })
+                .collect();
+            threads
+                .into_iter()
+                .map(|thread| thread.join().unwrap())
+                .collect()
+        });
+        for (index, result) in results.iter().enumerate() {
+            match result {
+                Ok(spirv) => {
+                    assert_eq!(index % 2, 0);
+                    instruction_lengths(spirv);
+                }
+                Err(error) => {
+                    assert_eq!(index % 2, 1);
+                    let log = error.log();
+                    assert!(log.contains(&format!("missing{}", index)), "{}", log);
+                    assert!(!log.contains(&format!("missing{}", 4 - index)), "{}", log);
+                }
+            }
+        }
     }
-    .into()
 }
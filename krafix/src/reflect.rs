@@ -0,0 +1,673 @@
+//! Reflection on compiled SPIR-V, which lists the inputs, uniforms and samplers of a shader.
+
+use std::{collections::HashMap, fmt};
+
+const MAGIC: u32 = 0x0723_0203;
+
+const OP_NAME: u32 = 5;
+const OP_MEMBER_NAME: u32 = 6;
+const OP_EXECUTION_MODE: u32 = 16;
+const OP_TYPE_BOOL: u32 = 20;
+const OP_TYPE_INT: u32 = 21;
+const OP_TYPE_FLOAT: u32 = 22;
+const OP_TYPE_VECTOR: u32 = 23;
+const OP_TYPE_MATRIX: u32 = 24;
+const OP_TYPE_IMAGE: u32 = 25;
+const OP_TYPE_SAMPLED_IMAGE: u32 = 27;
+const OP_TYPE_ARRAY: u32 = 28;
+const OP_TYPE_STRUCT: u32 = 30;
+const OP_TYPE_POINTER: u32 = 32;
+const OP_CONSTANT: u32 = 43;
+const OP_VARIABLE: u32 = 59;
+const OP_DECORATE: u32 = 71;
+const OP_MEMBER_DECORATE: u32 = 72;
+
+const EXECUTION_MODE_LOCAL_SIZE: u32 = 17;
+
+const DECORATION_BUILT_IN: u32 = 11;
+const DECORATION_LOCATION: u32 = 30;
+const DECORATION_BINDING: u32 = 33;
+const DECORATION_DESCRIPTOR_SET: u32 = 34;
+const DECORATION_OFFSET: u32 = 35;
+
+const STORAGE_CLASS_UNIFORM_CONSTANT: u32 = 0;
+const STORAGE_CLASS_INPUT: u32 = 1;
+const STORAGE_CLASS_UNIFORM: u32 = 2;
+const STORAGE_CLASS_PUSH_CONSTANT: u32 = 9;
+
+const DIM_2D: u32 = 1;
+const DIM_3D: u32 = 2;
+const DIM_CUBE: u32 = 3;
+
+/// The type of a shader variable.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DataType {
+    Bool,
+    Int,
+    IVec2,
+    IVec3,
+    IVec4,
+    UInt,
+    UVec2,
+    UVec3,
+    UVec4,
+    Float,
+    Vec2,
+    Vec3,
+    Vec4,
+    Mat2,
+    Mat3,
+    Mat4,
+    Sampler2D,
+    Sampler2DArray,
+    Sampler3D,
+    SamplerCube,
+    Struct,
+    /// A type without a counterpart in Kinc, like double precision vectors.
+    Other,
+}
+
+impl DataType {
+    /// The GLSL name, like `vec3` or `sampler2DArray`.
+    pub fn name(self) -> &'static str {
+        match self {
+            DataType::Bool => "bool",
+            DataType::Int => "int",
+            DataType::IVec2 => "ivec2",
+            DataType::IVec3 => "ivec3",
+            DataType::IVec4 => "ivec4",
+            DataType::UInt => "uint",
+            DataType::UVec2 => "uvec2",
+            DataType::UVec3 => "uvec3",
+            DataType::UVec4 => "uvec4",
+            DataType::Float => "float",
+            DataType::Vec2 => "vec2",
+            DataType::Vec3 => "vec3",
+            DataType::Vec4 => "vec4",
+            DataType::Mat2 => "mat2",
+            DataType::Mat3 => "mat3",
+            DataType::Mat4 => "mat4",
+            DataType::Sampler2D => "sampler2D",
+            DataType::Sampler2DArray => "sampler2DArray",
+            DataType::Sampler3D => "sampler3D",
+            DataType::SamplerCube => "samplerCube",
+            DataType::Struct => "struct",
+            DataType::Other => "other",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Attribute {
+    pub name: String,
+    pub location: u32,
+    pub ty: DataType,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Uniform {
+    pub name: String,
+    pub ty: DataType,
+    /// The array length, 1 for uniforms that aren't arrays.
+    pub count: u32,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Sampler {
+    pub name: String,
+    pub binding: Option<u32>,
+    pub set: Option<u32>,
+    pub ty: DataType,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Reflection {
+    /// The inputs of the stage without built-ins, sorted by location,
+    /// for vertex shaders these are the vertex attributes.
+    pub attributes: Vec<Attribute>,
+    /// Loose uniforms and the members of uniform blocks, in declaration order.
+    pub uniforms: Vec<Uniform>,
+    /// Sorted by binding.
+    pub samplers: Vec<Sampler>,
+    /// The local size of compute shaders.
+    pub workgroup_size: Option<[u32; 3]>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ReflectError {
+    NotSpirv,
+    Truncated,
+}
+
+impl fmt::Display for ReflectError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReflectError::NotSpirv => f.write_str("the data is not SPIR-V"),
+            ReflectError::Truncated => f.write_str("the SPIR-V module is truncated"),
+        }
+    }
+}
+
+impl std::error::Error for ReflectError {}
+
+#[derive(Copy, Clone)]
+enum Type {
+    Bool,
+    Int { signed: bool },
+    Float { width: u32 },
+    Vector { component: u32, count: u32 },
+    Matrix { column: u32, count: u32 },
+    Image { dim: u32, arrayed: bool },
+    SampledImage { image: u32 },
+    Array { element: u32, length: u32 },
+    Struct,
+    Pointer { pointee: u32 },
+}
+
+#[derive(Default)]
+struct Module {
+    names: HashMap<u32, String>,
+    member_names: HashMap<(u32, u32), String>,
+    locations: HashMap<u32, u32>,
+    bindings: HashMap<u32, u32>,
+    sets: HashMap<u32, u32>,
+    built_ins: Vec<u32>,
+    /// Struct types with built-in members, like `gl_PerVertex`.
+    built_in_structs: Vec<u32>,
+    /// Member offsets, only present for uniform blocks.
+    offsets: HashMap<u32, Vec<(u32, u32)>>,
+    types: HashMap<u32, Type>,
+    struct_members: HashMap<u32, Vec<u32>>,
+    constants: HashMap<u32, u32>,
+    /// The variables as (id, pointer type, storage class) in declaration order.
+    variables: Vec<(u32, u32, u32)>,
+    workgroup_size: Option<[u32; 3]>,
+}
+
+/// Reads the reflection data from a SPIR-V module.
+pub fn reflect(spirv: &[u8]) -> Result<Reflection, ReflectError> {
+    let module = Module::parse(&words(spirv)?)?;
+    Ok(module.reflection())
+}
+
+fn words(spirv: &[u8]) -> Result<Vec<u32>, ReflectError> {
+    if spirv.len() < 20 || !spirv.len().is_multiple_of(4) {
+        return Err(ReflectError::NotSpirv);
+    }
+    let magic = [spirv[0], spirv[1], spirv[2], spirv[3]];
+    let read: fn([u8; 4]) -> u32 = if u32::from_le_bytes(magic) == MAGIC {
+        u32::from_le_bytes
+    } else if u32::from_be_bytes(magic) == MAGIC {
+        u32::from_be_bytes
+    } else {
+        return Err(ReflectError::NotSpirv);
+    };
+    Ok(spirv
+        .chunks_exact(4)
+        .map(|word| read([word[0], word[1], word[2], word[3]]))
+        .collect())
+}
+
+/// Decodes a null-terminated string packed into words.
+fn string(words: &[u32]) -> String {
+    let bytes: Vec<u8> = words
+        .iter()
+        .flat_map(|word| word.to_le_bytes())
+        .take_while(|&byte| byte != 0)
+        .collect();
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+impl Module {
+    fn parse(words: &[u32]) -> Result<Self, ReflectError> {
+        let mut module = Module::default();
+        // Skips the header
+        let mut rest = &words[5..];
+        while let Some(&first) = rest.first() {
+            let count = (first >> 16) as usize;
+            if count == 0 || count > rest.len() {
+                return Err(ReflectError::Truncated);
+            }
+            let opcode = first & 0xFFFF;
+            module.instruction(opcode, &rest[1..count]);
+            rest = &rest[count..];
+        }
+        Ok(module)
+    }
+
+    fn instruction(&mut self, opcode: u32, operands: &[u32]) {
+        let operand = |index: usize| operands.get(index).copied().unwrap_or(0);
+        match opcode {
+            OP_NAME if !operands.is_empty() => {
+                self.names.insert(operands[0], string(&operands[1..]));
+            }
+            OP_MEMBER_NAME if operands.len() >= 2 => {
+                self.member_names
+                    .insert((operands[0], operands[1]), string(&operands[2..]));
+            }
+            OP_EXECUTION_MODE if operand(1) == EXECUTION_MODE_LOCAL_SIZE => {
+                self.workgroup_size = Some([operand(2), operand(3), operand(4)]);
+            }
+            OP_TYPE_BOOL => {
+                self.types.insert(operand(0), Type::Bool);
+            }
+            OP_TYPE_INT => {
+                self.types.insert(
+                    operand(0),
+                    Type::Int {
+                        signed: operand(2) != 0,
+                    },
+                );
+            }
+            OP_TYPE_FLOAT => {
+                self.types
+                    .insert(operand(0), Type::Float { width: operand(1) });
+            }
+            OP_TYPE_VECTOR => {
+                self.types.insert(
+                    operand(0),
+                    Type::Vector {
+                        component: operand(1),
+                        count: operand(2),
+                    },
+                );
+            }
+            OP_TYPE_MATRIX => {
+                self.types.insert(
+                    operand(0),
+                    Type::Matrix {
+                        column: operand(1),
+                        count: operand(2),
+                    },
+                );
+            }
+            OP_TYPE_IMAGE => {
+                self.types.insert(
+                    operand(0),
+                    Type::Image {
+                        dim: operand(2),
+                        arrayed: operand(4) != 0,
+                    },
+                );
+            }
+            OP_TYPE_SAMPLED_IMAGE => {
+                self.types
+                    .insert(operand(0), Type::SampledImage { image: operand(1) });
+            }
+            OP_TYPE_ARRAY => {
+                self.types.insert(
+                    operand(0),
+                    Type::Array {
+                        element: operand(1),
+                        length: operand(2),
+                    },
+                );
+            }
+            OP_TYPE_STRUCT if !operands.is_empty() => {
+                self.types.insert(operands[0], Type::Struct);
+                self.struct_members
+                    .insert(operands[0], operands[1..].to_vec());
+            }
+            OP_TYPE_POINTER => {
+                self.types.insert(
+                    operand(0),
+                    Type::Pointer {
+                        pointee: operand(2),
+                    },
+                );
+            }
+            OP_CONSTANT => {
+                self.constants.insert(operand(1), operand(2));
+            }
+            OP_VARIABLE => {
+                self.variables.push((operand(1), operand(0), operand(2)));
+            }
+            OP_DECORATE => match operand(1) {
+                DECORATION_BUILT_IN => self.built_ins.push(operand(0)),
+                DECORATION_LOCATION => {
+                    self.locations.insert(operand(0), operand(2));
+                }
+                DECORATION_BINDING => {
+                    self.bindings.insert(operand(0), operand(2));
+                }
+                DECORATION_DESCRIPTOR_SET => {
+                    self.sets.insert(operand(0), operand(2));
+                }
+                _ => {}
+            },
+            OP_MEMBER_DECORATE => match operand(2) {
+                DECORATION_BUILT_IN => self.built_in_structs.push(operand(0)),
+                DECORATION_OFFSET => {
+                    self.offsets
+                        .entry(operand(0))
+                        .or_default()
+                        .push((operand(1), operand(3)));
+                }
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+
+    /// Strips arrays from a type, returns the element type and the total length.
+    fn element(&self, mut ty: u32) -> (u32, u32) {
+        let mut count = 1;
+        while let Some(Type::Array { element, length }) = self.types.get(&ty) {
+            count *= self.constants.get(length).copied().unwrap_or(1);
+            ty = *element;
+        }
+        (ty, count)
+    }
+
+    fn data_type(&self, ty: u32) -> DataType {
+        let scalar = |ty: u32| self.types.get(&ty).copied();
+        match self.types.get(&ty) {
+            Some(Type::Bool) => DataType::Bool,
+            Some(Type::Int { signed: true }) => DataType::Int,
+            Some(Type::Int { signed: false }) => DataType::UInt,
+            Some(Type::Float { width: 32 }) => DataType::Float,
+            Some(Type::Vector { component, count }) => match (scalar(*component), count) {
+                (Some(Type::Int { signed: true }), 2) => DataType::IVec2,
+                (Some(Type::Int { signed: true }), 3) => DataType::IVec3,
+                (Some(Type::Int { signed: true }), 4) => DataType::IVec4,
+                (Some(Type::Int { signed: false }), 2) => DataType::UVec2,
+                (Some(Type::Int { signed: false }), 3) => DataType::UVec3,
+                (Some(Type::Int { signed: false }), 4) => DataType::UVec4,
+                (Some(Type::Float { width: 32 }), 2) => DataType::Vec2,
+                (Some(Type::Float { width: 32 }), 3) => DataType::Vec3,
+                (Some(Type::Float { width: 32 }), 4) => DataType::Vec4,
+                _ => DataType::Other,
+            },
+            Some(Type::Matrix { column, count }) => match (self.data_type(*column), count) {
+                (DataType::Vec2, 2) => DataType::Mat2,
+                (DataType::Vec3, 3) => DataType::Mat3,
+                (DataType::Vec4, 4) => DataType::Mat4,
+                _ => DataType::Other,
+            },
+            Some(Type::SampledImage { image }) => match self.types.get(image) {
+                Some(Type::Image {
+                    dim: DIM_2D,
+                    arrayed: false,
+                }) => DataType::Sampler2D,
+                Some(Type::Image {
+                    dim: DIM_2D,
+                    arrayed: true,
+                }) => DataType::Sampler2DArray,
+                Some(Type::Image { dim: DIM_3D, .. }) => DataType::Sampler3D,
+                Some(Type::Image { dim: DIM_CUBE, .. }) => DataType::SamplerCube,
+                _ => DataType::Other,
+            },
+            Some(Type::Struct) => DataType::Struct,
+            _ => DataType::Other,
+        }
+    }
+
+    fn name(&self, id: u32) -> String {
+        self.names.get(&id).cloned().unwrap_or_default()
+    }
+
+    fn reflection(&self) -> Reflection {
+        let mut reflection = Reflection {
+            workgroup_size: self.workgroup_size,
+            ..Reflection::default()
+        };
+        for &(id, pointer, storage_class) in &self.variables {
+            let Some(Type::Pointer { pointee }) = self.types.get(&pointer) else {
+                continue;
+            };
+            let (ty, count) = self.element(*pointee);
+            match storage_class {
+                STORAGE_CLASS_INPUT => {
+                    let name = self.name(id);
+                    if self.built_ins.contains(&id)
+                        || self.built_in_structs.contains(&ty)
+                        || name.starts_with("gl_")
+                    {
+                        continue;
+                    }
+                    reflection.attributes.push(Attribute {
+                        name,
+                        location: self.locations.get(&id).copied().unwrap_or(0),
+                        ty: self.data_type(ty),
+                    });
+                }
+                STORAGE_CLASS_UNIFORM_CONSTANT => match self.data_type(ty) {
+                    sampler @ (DataType::Sampler2D
+                    | DataType::Sampler2DArray
+                    | DataType::Sampler3D
+                    | DataType::SamplerCube) => reflection.samplers.push(Sampler {
+                        name: self.name(id),
+                        binding: self.bindings.get(&id).copied(),
+                        set: self.sets.get(&id).copied(),
+                        ty: sampler,
+                    }),
+                    ty => reflection.uniforms.push(Uniform {
+                        name: self.name(id),
+                        ty,
+                        count,
+                    }),
+                },
+                STORAGE_CLASS_UNIFORM | STORAGE_CLASS_PUSH_CONSTANT => {
+                    self.block_uniforms(ty, &mut reflection.uniforms)
+                }
+                _ => {}
+            }
+        }
+        reflection
+            .attributes
+            .sort_by_key(|attribute| attribute.location);
+        reflection
+            .samplers
+            .sort_by_key(|sampler| (sampler.set, sampler.binding));
+        reflection
+    }
+
+    /// Adds the members of a uniform block, krafix puts loose uniforms into one when compiling to SPIR-V.
+    fn block_uniforms(&self, block: u32, uniforms: &mut Vec<Uniform>) {
+        let Some(members) = self.struct_members.get(&block) else {
+            return;
+        };
+        let mut order: Vec<usize> = (0..members.len()).collect();
+        if let Some(offsets) = self.offsets.get(&block) {
+            let offset = |member: usize| {
+                offsets
+                    .iter()
+                    .find(|(index, _)| *index as usize == member)
+                    .map(|(_, offset)| *offset)
+            };
+            order.sort_by_key(|&member| offset(member));
+        }
+        for member in order {
+            let (ty, count) = self.element(members[member]);
+            uniforms.push(Uniform {
+                name: self
+                    .member_names
+                    .get(&(block, member as u32))
+                    .cloned()
+                    .unwrap_or_default(),
+                ty: self.data_type(ty),
+                count,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Options, ShaderKind, Target};
+
+    /// Compiles `source` with glslang and reflects the result.
+    fn reflection(kind: ShaderKind, source: &str) -> Reflection {
+        let spirv = Options::new(kind, Target::Spirv)
+            .version(450)
+            .compile(source)
+            .unwrap();
+        reflect(&spirv).unwrap()
+    }
+
+    fn uniform(name: &str, ty: DataType, count: u32) -> Uniform {
+        Uniform {
+            name: name.to_owned(),
+            ty,
+            count,
+        }
+    }
+
+    #[test]
+    fn attributes_are_sorted_by_location_without_built_ins() {
+        let reflection = reflection(
+            ShaderKind::Vertex,
+            "#version 450
+layout(location = 0) in vec3 pos;
+layout(location = 2) in vec4 color;
+layout(location = 1) in vec2 uv;
+layout(location = 3) in ivec4 bones;
+layout(location = 4) in float weight;
+layout(location = 0) out vec2 uv_out;
+void main() {
+    uv_out = uv + vec2(bones.xy) * weight;
+    gl_Position = vec4(pos, float(gl_VertexIndex)) * color;
+}
+",
+        );
+        let attributes: Vec<_> = reflection
+            .attributes
+            .iter()
+            .map(|attribute| (attribute.name.as_str(), attribute.location, attribute.ty))
+            .collect();
+        assert_eq!(
+            attributes,
+            [
+                ("pos", 0, DataType::Vec3),
+                ("uv", 1, DataType::Vec2),
+                ("color", 2, DataType::Vec4),
+                ("bones", 3, DataType::IVec4),
+                ("weight", 4, DataType::Float),
+            ]
+        );
+        assert!(reflection.uniforms.is_empty());
+        assert!(reflection.samplers.is_empty());
+        assert_eq!(reflection.workgroup_size, None);
+    }
+
+    #[test]
+    fn lists_uniform_block_members_with_arrays_and_structs() {
+        let reflection = reflection(
+            ShaderKind::Vertex,
+            "#version 450
+struct Light {
+    vec3 color;
+    float power;
+};
+layout(set = 0, binding = 0) uniform Uniforms {
+    mat4 mvp;
+    Light lights[2];
+    float weights[4];
+    vec2 grid[2][3];
+    mat3 normal;
+    uvec2 size;
+    int mode;
+    bool enabled;
+};
+layout(location = 0) in vec3 pos;
+void main() {
+    vec3 lit = lights[1].color * lights[0].power * weights[3] * grid[1][2].x;
+    lit = normal * lit * float(size.x) * float(mode) * float(enabled);
+    gl_Position = mvp * vec4(pos * lit, 1.0);
+}
+",
+        );
+        assert_eq!(
+            reflection.uniforms,
+            [
+                uniform("mvp", DataType::Mat4, 1),
+                uniform("lights", DataType::Struct, 2),
+                uniform("weights", DataType::Float, 4),
+                uniform("grid", DataType::Vec2, 6),
+                uniform("normal", DataType::Mat3, 1),
+                uniform("size", DataType::UVec2, 1),
+                uniform("mode", DataType::Int, 1),
+                // SPIR-V has no bools in uniform blocks, glslang stores them as uints
+                uniform("enabled", DataType::UInt, 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn samplers_are_sorted_by_binding() {
+        let reflection = reflection(
+            ShaderKind::Fragment,
+            "#version 450
+layout(set = 0, binding = 2) uniform sampler2D albedo;
+layout(set = 0, binding = 0) uniform samplerCube sky;
+layout(set = 0, binding = 1) uniform sampler2DArray layers;
+layout(set = 0, binding = 3) uniform sampler3D volume;
+layout(location = 0) in vec3 uv;
+layout(location = 0) out vec4 color;
+void main() {
+    color = texture(albedo, uv.xy) * texture(sky, uv) * texture(layers, uv) * texture(volume, uv);
+}
+",
+        );
+        let samplers: Vec<_> = reflection
+            .samplers
+            .iter()
+            .map(|sampler| {
+                (
+                    sampler.name.as_str(),
+                    sampler.binding,
+                    sampler.set,
+                    sampler.ty,
+                )
+            })
+            .collect();
+        assert_eq!(
+            samplers,
+            [
+                ("sky", Some(0), Some(0), DataType::SamplerCube),
+                ("layers", Some(1), Some(0), DataType::Sampler2DArray),
+                ("albedo", Some(2), Some(0), DataType::Sampler2D),
+                ("volume", Some(3), Some(0), DataType::Sampler3D),
+            ]
+        );
+        // The inputs of later stages are listed like vertex attributes
+        assert_eq!(
+            reflection.attributes,
+            [Attribute {
+                name: "uv".to_owned(),
+                location: 0,
+                ty: DataType::Vec3,
+            }]
+        );
+        assert!(reflection.uniforms.is_empty());
+    }
+
+    #[test]
+    fn reads_the_workgroup_size() {
+        let reflection = reflection(
+            ShaderKind::Compute,
+            "#version 450
+layout(local_size_x = 8, local_size_y = 4) in;
+void main() {}
+",
+        );
+        assert_eq!(reflection.workgroup_size, Some([8, 4, 1]));
+        assert!(reflection.attributes.is_empty());
+    }
+
+    #[test]
+    fn rejects_other_data() {
+        assert_eq!(
+            reflect(b"#version 450\nvoid main() {}\n"),
+            Err(ReflectError::NotSpirv)
+        );
+
+        let spirv = Options::new(ShaderKind::Compute, Target::Spirv)
+            .version(450)
+            .compile("#version 450\nlayout(local_size_x = 1) in;\nvoid main() {}\n")
+            .unwrap();
+        // Ends in the middle of the first instruction, `OpCapability Shader`
+        assert_eq!(reflect(&spirv[..24]), Err(ReflectError::Truncated));
+    }
+}